@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linkstore::PointerWidth;
+
+fuzz_target!(|data: &[u8]| {
+	linkstore::__fuzz::decode_section(data, PointerWidth::Bits32);
+	linkstore::__fuzz::decode_section(data, PointerWidth::Bits64);
+});
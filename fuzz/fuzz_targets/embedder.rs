@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linkstore::Embedder;
+
+fuzz_target!(|data: &[u8]| {
+	let mut data = data.to_vec();
+	let mut binary = std::io::Cursor::new(&mut data[..]);
+	let mut embedder = match Embedder::new(&mut binary) {
+		Ok(embedder) => embedder,
+		Err(_) => return,
+	};
+
+	// Write every discovered linkstore back into the binary
+	let manifest = embedder.export();
+	if embedder.import(&manifest).is_ok() {
+		let _ = embedder.finish();
+	}
+});
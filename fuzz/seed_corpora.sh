@@ -0,0 +1,64 @@
+#!/usr/bin/env bash
+# Builds the test binaries in `tests/` for every installed target and copies them into the fuzz corpora,
+# so that each fuzz target starts from real binaries containing linkstores.
+#
+# Usage: fuzz/seed_corpora.sh [target...]
+# Defaults to every target installed with rustup.
+
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+corpus=fuzz/corpus
+profile=linkstore-test-release
+targets=("$@")
+if [ ${#targets[@]} -eq 0 ]; then
+	mapfile -t targets < <(rustup target list --installed)
+fi
+
+mkdir -p "$corpus"/{embedder,decode_section,elf,pe,coff,macho,ar,wasm}
+
+for target in "${targets[@]}"; do
+	if ! cargo build --profile "$profile" --examples --target "$target" --manifest-path tests/Cargo.toml --target-dir tests/target; then
+		echo "Skipping $target, which failed to build" >&2
+		continue
+	fi
+
+	for binary in tests/target/"$target"/"$profile"/examples/*; do
+		[ -f "$binary" ] || continue
+
+		case "$binary" in
+			*.d | *.pdb | *.exp | *.unstamped) continue ;;
+			*.a | *.lib) format=ar ;;
+			*.wasm) format=wasm ;;
+			*.dylib) format=macho ;;
+			*.dll | *.exe) format=pe ;;
+			*)
+				case "$target" in
+					*-apple-*) format=macho ;;
+					*-windows-*) format=pe ;;
+					*) format=elf ;;
+				esac
+				;;
+		esac
+
+		name="$target-$(basename "$binary")"
+		cp "$binary" "$corpus/$format/$name"
+		cp "$binary" "$corpus/embedder/$name"
+
+		# The raw linkstore section seeds the container decoder
+		if [ "$format" = elf ] && command -v objcopy > /dev/null; then
+			objcopy -O binary --only-section=.lnkstre "$binary" "$corpus/decode_section/$name" 2>/dev/null || true
+		fi
+
+		# Archive members are COFF objects on Windows, so seed the COFF corpus with them too
+		if [ "$format" = ar ] && [[ "$target" = *-windows-msvc ]]; then
+			members=$(mktemp -d)
+			(cd "$members" && ar x "$OLDPWD/$binary" 2>/dev/null) || true
+			for member in "$members"/*; do
+				[ -f "$member" ] && cp "$member" "$corpus/coff/$name-$(basename "$member")"
+			done
+			rm -rf "$members"
+		fi
+	done
+done
@@ -0,0 +1,84 @@
+//! Parses the containers emitted by `linkstore!`, for both the embedder and runtime lookups.
+//!
+//! ```text
+//! [MAGIC][name][NUL][size: usize][padding: usize][checksum: u32][padding bytes][value]
+//! ```
+//!
+//! The size and padding are stored as the target's `usize`, so parsing depends on its pointer width.
+
+use super::encode::{header_checksum, MAGIC, MAX_NAME_LEN};
+
+/// A linkstore parsed from the start of a byte slice.
+pub(crate) struct Container<'b> {
+	pub(crate) name: &'b [u8],
+
+	/// The offset of the value from the start of the container
+	pub(crate) value_offset: usize,
+	pub(crate) value: &'b [u8],
+}
+impl Container<'_> {
+	/// The offset of the first byte after the container
+	#[inline]
+	pub(crate) fn end(&self) -> usize {
+		self.value_offset + self.value.len()
+	}
+}
+
+/// The size of the smallest possible container header, which has an empty name.
+pub(crate) const fn minimum_header_size(pointer_width: usize) -> usize {
+	MAGIC.len() + 1 + (pointer_width * 2) + 4
+}
+
+/// Parses the container at the start of `bytes`, where `pointer_width` is the size of the target's `usize` in bytes.
+///
+/// Returns `None` if the bytes are not a valid container, because its magic bytes or checksum don't match, its name is longer than [`MAX_NAME_LEN`] or it overruns the slice.
+pub(crate) fn parse(bytes: &[u8], pointer_width: usize) -> Option<Container<'_>> {
+	let rest = bytes.strip_prefix(&MAGIC)?;
+
+	// Bounded, so that scanning bytes full of false matches stays linear
+	let nul = rest.iter().take(MAX_NAME_LEN + 1).position(|byte| *byte == 0)?;
+	let name = &rest[..nul];
+	let rest = &rest[nul + 1..];
+
+	let size = rest.get(..pointer_width)?;
+	let padding = rest.get(pointer_width..pointer_width * 2)?;
+	let checksum = rest.get(pointer_width * 2..pointer_width * 2 + 4)?;
+
+	if u32::from_le_bytes(checksum.try_into().unwrap()) != header_checksum(name, size, padding) {
+		return None;
+	}
+
+	let read_usize = |bytes: &[u8]| {
+		let mut buf = [0u8; 8];
+		buf[..bytes.len()].copy_from_slice(bytes);
+		usize::try_from(u64::from_le_bytes(buf)).ok()
+	};
+
+	let value_offset = (bytes.len() - rest.len() + pointer_width * 2 + 4).checked_add(read_usize(padding)?)?;
+	let value = bytes.get(value_offset..value_offset.checked_add(read_usize(size)?)?)?;
+
+	Some(Container { name, value_offset, value })
+}
+
+/// Scans `bytes` for containers by searching for their magic bytes.
+///
+/// Bytes which look like a container but fail to parse are skipped, and scanning resumes from the byte after them.
+pub(crate) fn scan(bytes: &[u8], pointer_width: usize) -> impl Iterator<Item = (usize, Container<'_>)> {
+	let mut offset = 0;
+	core::iter::from_fn(move || loop {
+		offset += bytes.get(offset..)?.iter().position(|byte| *byte == MAGIC[0])?;
+		if bytes.len() - offset < minimum_header_size(pointer_width) {
+			return None;
+		}
+
+		match parse(&bytes[offset..], pointer_width) {
+			Some(container) => {
+				let found = offset;
+				offset += container.end();
+				return Some((found, container));
+			}
+			// Not a container, so resynchronise from the byte after the false match
+			None => offset += 1,
+		}
+	})
+}
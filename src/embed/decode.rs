@@ -1,4 +1,8 @@
-use core::mem::MaybeUninit;
+use super::encode::EncodeLinkstore;
+use core::{
+	mem::MaybeUninit,
+	num::{NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8},
+};
 
 /// Implemented for types that can be decoded from a linkstore.
 ///
@@ -7,6 +11,15 @@ use core::mem::MaybeUninit;
 /// Implementing this trait is extremely unsafe. The bytes will be effectively [`core::mem::transmute`]d into the type in the compiled binary, so the bytes must be valid and converted to big-endian if we're on a big-endian machine.
 pub unsafe trait DecodeLinkstore: Sized + TryDecodeLinkstore {
 	fn from_le_bytes(bytes: &[u8]) -> Self;
+
+	/// Decodes a value from a binary whose `usize` is `pointer_width` bytes.
+	///
+	/// This only differs from [`DecodeLinkstore::from_le_bytes`] for types containing `usize` or `isize`, whose size depends on the target.
+	#[doc(hidden)]
+	fn from_target_le_bytes(bytes: &[u8], pointer_width: usize) -> Self {
+		let _ = pointer_width;
+		Self::from_le_bytes(bytes)
+	}
 }
 
 /// Implemented for types that can be decoded from a linkstore, but may be fallible.
@@ -19,8 +32,29 @@ pub unsafe trait DecodeLinkstore: Sized + TryDecodeLinkstore {
 pub unsafe trait TryDecodeLinkstore: Sized {
 	type Error;
 	fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error>;
+
+	/// Decodes a value from a binary whose `usize` is `pointer_width` bytes.
+	///
+	/// This only differs from [`TryDecodeLinkstore::try_from_le_bytes`] for types containing `usize` or `isize`, whose size depends on the target.
+	#[doc(hidden)]
+	fn try_from_target_le_bytes(bytes: &[u8], pointer_width: usize) -> Result<Self, Self::Error> {
+		let _ = pointer_width;
+		Self::try_from_le_bytes(bytes)
+	}
 }
 
+/// Implemented for types that are valid for any bit pattern, such as numbers, so that a linkstore's value can be safely referenced in place.
+///
+/// `linkstore!` only allows the unsafe `get()` reference getter to be called for these types, because the bytes in the binary are never validated.
+/// Other types must be read with `try_get()`, which decodes them with [`TryDecodeLinkstore`].
+///
+/// ## Safety
+///
+/// Every bit pattern of `size_of::<Self>()` bytes must be a valid value of the type. For example, `bool` must not implement this trait because only `0` and `1` are valid.
+pub unsafe trait AnyBitPattern: Sized {}
+
+unsafe impl<T: AnyBitPattern, const N: usize> AnyBitPattern for [T; N] {}
+
 /// Automatically implements an `core::convert::Infallible` implementation of `TryDecodeLinkstore` during implementation of `DecodeLinkstore`
 macro_rules! infallible_decode {
 	(unsafe impl DecodeLinkstore for $ty:ty {$($tt:tt)+}) => {
@@ -31,12 +65,16 @@ macro_rules! infallible_decode {
 			fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
 				Ok(<$ty as DecodeLinkstore>::from_le_bytes(bytes))
 			}
+
+			fn try_from_target_le_bytes(bytes: &[u8], pointer_width: usize) -> Result<Self, Self::Error> {
+				Ok(<$ty as DecodeLinkstore>::from_target_le_bytes(bytes, pointer_width))
+			}
 		}
 	};
 }
 
 /// Errors that can occur when decoding an array stored in a linkstore
-#[derive(thiserror::Error)]
+#[derive(thiserror::Error, Debug)]
 pub enum TryDecodeLinkstoreArrayError<E> {
 	#[error("array had {0} elements, expected {1}")]
 	MismatchedElementCount(usize, usize),
@@ -47,35 +85,139 @@ pub enum TryDecodeLinkstoreArrayError<E> {
 	#[error("{0}")]
 	Other(#[from] E),
 }
-unsafe impl<T: TryDecodeLinkstore, const N: usize> TryDecodeLinkstore for [T; N] {
+unsafe impl<T: TryDecodeLinkstore + EncodeLinkstore, const N: usize> TryDecodeLinkstore for [T; N] {
 	type Error = TryDecodeLinkstoreArrayError<<T as TryDecodeLinkstore>::Error>;
 
 	fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
-		if bytes.len() % core::mem::size_of::<T>() != 0 {
-			return Err(TryDecodeLinkstoreArrayError::MismatchedBytesCount(bytes.len(), core::mem::size_of::<T>()));
+		try_decode_array(bytes, core::mem::size_of::<T>(), T::try_from_le_bytes)
+	}
+
+	fn try_from_target_le_bytes(bytes: &[u8], pointer_width: usize) -> Result<Self, Self::Error> {
+		// The size of the elements depends on the target, so the array must be exactly as big as the target's
+		let size = T::encoded_size(pointer_width);
+		if bytes.len() != N * size {
+			return Err(TryDecodeLinkstoreArrayError::MismatchedBytesCount(bytes.len(), size));
 		}
-		if bytes.len() < N * core::mem::size_of::<T>() {
-			return Err(TryDecodeLinkstoreArrayError::MismatchedElementCount(
-				bytes.len() / core::mem::size_of::<T>(),
-				N,
-			));
+		try_decode_array(bytes, size, |chunk| T::try_from_target_le_bytes(chunk, pointer_width))
+	}
+}
+
+/// Decodes an array whose elements are each `size` bytes.
+fn try_decode_array<T: TryDecodeLinkstore, const N: usize>(
+	bytes: &[u8],
+	size: usize,
+	decode: impl Fn(&[u8]) -> Result<T, T::Error>,
+) -> Result<[T; N], TryDecodeLinkstoreArrayError<T::Error>> {
+	// Zero-sized elements are each decoded from no bytes
+	if size != 0 && bytes.len() % size != 0 {
+		return Err(TryDecodeLinkstoreArrayError::MismatchedBytesCount(bytes.len(), size));
+	}
+	if bytes.len() < N * size {
+		return Err(TryDecodeLinkstoreArrayError::MismatchedElementCount(bytes.len() / size, N));
+	}
+
+	let mut result = unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() };
+	for (i, elem) in result.iter_mut().enumerate() {
+		unsafe { *elem.as_mut_ptr() = decode(&bytes[i * size..(i + 1) * size])? };
+	}
+	Ok(result.map(|elem| unsafe { elem.assume_init() }))
+}
+
+/// Errors that can occur when decoding a tuple stored in a linkstore
+#[derive(thiserror::Error, Debug)]
+pub enum TryDecodeLinkstoreTupleError {
+	/// One of the tuple's elements failed to decode
+	#[error("element {index} of tuple: {error}")]
+	Element {
+		/// The index of the element which failed to decode
+		index: usize,
+
+		/// The error from decoding the element
+		error: Box<dyn std::error::Error + Send + Sync>,
+	},
+
+	#[error("{0} bytes cannot be decoded into a tuple of {1} bytes")]
+	MismatchedBytesCount(usize, usize),
+}
+
+/// Fields are decoded from their offsets in the host's layout of the tuple.
+macro_rules! impl_tuples {
+	($(($($ty:ident $idx:tt),+))+) => {$(
+		unsafe impl<$($ty: TryDecodeLinkstore),+> TryDecodeLinkstore for ($($ty,)+)
+		where
+			$(<$ty as TryDecodeLinkstore>::Error: std::error::Error + Send + Sync + 'static),+
+		{
+			type Error = TryDecodeLinkstoreTupleError;
+
+			fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+				Ok(($({
+					let offset = core::mem::offset_of!(Self, $idx);
+					let field = bytes
+						.get(offset..offset + core::mem::size_of::<$ty>())
+						.ok_or(TryDecodeLinkstoreTupleError::MismatchedBytesCount(bytes.len(), core::mem::size_of::<Self>()))?;
+					$ty::try_from_le_bytes(field).map_err(|error| TryDecodeLinkstoreTupleError::Element { index: $idx, error: Box::new(error) })?
+				},)+))
+			}
 		}
 
-		let mut result = unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() };
-		for (i, chunk) in bytes.chunks(core::mem::size_of::<T>()).enumerate() {
-			unsafe { *result[i].as_mut_ptr() = T::try_from_le_bytes(chunk)? };
+		unsafe impl<$($ty: DecodeLinkstore),+> DecodeLinkstore for ($($ty,)+)
+		where
+			$(<$ty as TryDecodeLinkstore>::Error: std::error::Error + Send + Sync + 'static),+
+		{
+			fn from_le_bytes(bytes: &[u8]) -> Self {
+				($({
+					let offset = core::mem::offset_of!(Self, $idx);
+					$ty::from_le_bytes(&bytes[offset..offset + core::mem::size_of::<$ty>()])
+				},)+)
+			}
 		}
-		Ok(result.map(|elem| unsafe { elem.assume_init() }))
+
+		unsafe impl<$($ty: AnyBitPattern),+> AnyBitPattern for ($($ty,)+) {}
+	)+}
+}
+impl_tuples! {
+	(A 0)
+	(A 0, B 1)
+	(A 0, B 1, C 2)
+	(A 0, B 1, C 2, D 3)
+	(A 0, B 1, C 2, D 3, E 4)
+	(A 0, B 1, C 2, D 3, E 4, F 5)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+/// Errors that can occur when decoding a `bool` stored in a linkstore
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryDecodeLinkstoreBoolError {
+	/// The byte is neither `0` nor `1`
+	#[error("{0} is not a valid bool")]
+	Invalid(u8),
+
+	#[error("{0} bytes cannot be decoded into a bool")]
+	MismatchedBytesCount(usize),
+}
+
+unsafe impl DecodeLinkstore for bool {
+	fn from_le_bytes(bytes: &[u8]) -> Self {
+		bytes[0] != 0
 	}
 }
+unsafe impl TryDecodeLinkstore for bool {
+	type Error = TryDecodeLinkstoreBoolError;
 
-infallible_decode!(
-	unsafe impl DecodeLinkstore for bool {
-		fn from_le_bytes(bytes: &[u8]) -> Self {
-			bytes[0] != 0
+	fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+		match bytes {
+			[0] => Ok(false),
+			[1] => Ok(true),
+			[byte] => Err(TryDecodeLinkstoreBoolError::Invalid(*byte)),
+			_ => Err(TryDecodeLinkstoreBoolError::MismatchedBytesCount(bytes.len())),
 		}
 	}
-);
+}
 
 macro_rules! impl_numbers {
 	($($ty:ty),+) => {$(
@@ -84,6 +226,183 @@ macro_rules! impl_numbers {
 				<$ty>::from_le_bytes(bytes.try_into().unwrap())
 			}
 		});
+		unsafe impl AnyBitPattern for $ty {}
 	)+}
 }
 impl_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// Sign- or zero-extends an integer of up to 8 bytes to 64 bits.
+pub(crate) fn extend_pointer_sized(bytes: &[u8], signed: bool) -> [u8; 8] {
+	let fill = if signed && bytes.last().is_some_and(|byte| byte & 0x80 != 0) {
+		0xFF
+	} else {
+		0
+	};
+	let mut extended = [fill; 8];
+	extended[..bytes.len()].copy_from_slice(bytes);
+	extended
+}
+
+macro_rules! impl_pointer_sized {
+	($($ty:ty => $wide:ty),+) => {$(
+		infallible_decode!(unsafe impl DecodeLinkstore for $ty {
+			fn from_le_bytes(bytes: &[u8]) -> Self {
+				<$ty>::from_le_bytes(bytes.try_into().unwrap())
+			}
+
+			/// Values which are too big for the host are truncated.
+			fn from_target_le_bytes(bytes: &[u8], _pointer_width: usize) -> Self {
+				<$wide>::from_le_bytes(extend_pointer_sized(bytes, <$ty>::MIN != 0)) as $ty
+			}
+		});
+		unsafe impl AnyBitPattern for $ty {}
+	)+}
+}
+impl_pointer_sized!(usize => u64, isize => i64);
+
+/// Errors that can occur when decoding a `char` stored in a linkstore
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryDecodeLinkstoreCharError {
+	/// The value isn't a Unicode scalar value, such as a surrogate
+	#[error("{0:#x} is not a valid char")]
+	Invalid(u32),
+
+	#[error("{0} bytes cannot be decoded into a char")]
+	MismatchedBytesCount(usize),
+}
+
+unsafe impl TryDecodeLinkstore for char {
+	type Error = TryDecodeLinkstoreCharError;
+
+	fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+		let scalar = u32::from_le_bytes(
+			bytes
+				.try_into()
+				.map_err(|_| TryDecodeLinkstoreCharError::MismatchedBytesCount(bytes.len()))?,
+		);
+		char::from_u32(scalar).ok_or(TryDecodeLinkstoreCharError::Invalid(scalar))
+	}
+}
+
+/// Errors that can occur when decoding a `NonZero*` integer stored in a linkstore
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryDecodeLinkstoreNonZeroError {
+	#[error("value is zero")]
+	Zero,
+
+	#[error("{0} bytes cannot be decoded into an integer of {1} bytes")]
+	MismatchedBytesCount(usize, usize),
+}
+
+macro_rules! impl_non_zero {
+	($($ty:ty => $int:ty),+) => {$(
+		unsafe impl TryDecodeLinkstore for $ty {
+			type Error = TryDecodeLinkstoreNonZeroError;
+
+			fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+				let int = <$int>::from_le_bytes(
+					bytes
+						.try_into()
+						.map_err(|_| TryDecodeLinkstoreNonZeroError::MismatchedBytesCount(bytes.len(), core::mem::size_of::<$int>()))?,
+				);
+				<$ty>::new(int).ok_or(TryDecodeLinkstoreNonZeroError::Zero)
+			}
+		}
+
+		// `Option<NonZero*>` has the same layout as the integer, with `None` in place of zero
+		infallible_decode!(unsafe impl DecodeLinkstore for Option<$ty> {
+			fn from_le_bytes(bytes: &[u8]) -> Self {
+				<$ty>::new(<$int>::from_le_bytes(bytes.try_into().unwrap()))
+			}
+		});
+		unsafe impl AnyBitPattern for Option<$ty> {}
+	)+}
+}
+impl_non_zero!(
+	NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64, NonZeroU128 => u128,
+	NonZeroI8 => i8, NonZeroI16 => i16, NonZeroI32 => i32, NonZeroI64 => i64, NonZeroI128 => i128
+);
+
+/// Errors that can occur when decoding a fieldless enum stored in a linkstore
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryDecodeLinkstoreEnumError<R: core::fmt::Display> {
+	/// The discriminant doesn't match any variant
+	#[error("{0} is not a valid discriminant")]
+	Invalid(R),
+
+	#[error("{0} bytes cannot be decoded into a discriminant of {1} bytes")]
+	MismatchedBytesCount(usize, usize),
+}
+
+/// Defines a fieldless enum which can be stored in a linkstore, implementing [`EncodeLinkstore`](crate::EncodeLinkstore) and [`TryDecodeLinkstore`] without any unsafe code.
+///
+/// The enum must start with a `#[repr]` attribute of an integer type, which is how its discriminant is stored.
+/// Decoding fails with [`TryDecodeLinkstoreEnumError`] if the discriminant doesn't match any variant, or isn't the size of the `#[repr]` type.
+///
+/// ```
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore_enum! {
+///     #[repr(u8)]
+///     #[derive(Debug, PartialEq)]
+///     pub enum Mode {
+///         Fast = 1,
+///         Safe = 2,
+///     }
+/// }
+///
+/// linkstore! {
+///     pub static LINKSTORE_MODE: Mode = Mode::Fast;
+/// }
+///
+/// fn main() {
+///     assert_eq!(LINKSTORE_MODE::try_get(), Ok(Mode::Fast));
+/// }
+/// ```
+#[macro_export]
+macro_rules! linkstore_enum {
+	{
+		#[repr($repr:ident)]
+		$(#[$attr:meta])*
+		$vis:vis enum $name:ident {
+			$(
+				$(#[$variant_attr:meta])*
+				$variant:ident $(= $discriminant:expr)?
+			),+ $(,)?
+		}
+	} => {
+		#[repr($repr)]
+		$(#[$attr])*
+		$vis enum $name {
+			$(
+				$(#[$variant_attr])*
+				$variant $(= $discriminant)?
+			),+
+		}
+
+		unsafe impl $crate::EncodeLinkstore for $name {
+			fn as_le_bytes(&self) -> ::std::borrow::Cow<'_, [u8]> {
+				let discriminant = match self {
+					$(Self::$variant => Self::$variant as $repr),+
+				};
+				::std::borrow::Cow::Owned(discriminant.to_le_bytes().to_vec())
+			}
+		}
+
+		unsafe impl $crate::TryDecodeLinkstore for $name {
+			type Error = $crate::TryDecodeLinkstoreEnumError<$repr>;
+
+			fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+				let discriminant = <$repr>::from_le_bytes(bytes.try_into().map_err(|_| {
+					$crate::TryDecodeLinkstoreEnumError::MismatchedBytesCount(bytes.len(), ::core::mem::size_of::<$repr>())
+				})?);
+				$(
+					if discriminant == Self::$variant as $repr {
+						return Ok(Self::$variant);
+					}
+				)+
+				Err($crate::TryDecodeLinkstoreEnumError::Invalid(discriminant))
+			}
+		}
+	};
+}
@@ -0,0 +1,241 @@
+use super::*;
+use core::ops::Range;
+use std::path::PathBuf;
+
+/// The kind of identifier updated by [`EmbedderBuilder::update_build_id`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BuildIdKind {
+	/// The descriptor of an ELF `NT_GNU_BUILD_ID` note
+	GnuBuildId,
+
+	/// The UUID of a Mach-O `LC_UUID` load command
+	MachOUuid,
+}
+
+/// A build identifier which was replaced when the binary was stamped, reported by [`Embedder::finish_with_report`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildIdUpdate {
+	/// The kind of identifier
+	pub kind: BuildIdKind,
+
+	/// The CPU architecture of the object file containing the identifier, which distinguishes the slices of a fat binary
+	pub arch: Architecture,
+
+	/// The file offset of the identifier
+	pub offset: u64,
+
+	/// The identifier before the binary was stamped
+	pub old: Vec<u8>,
+
+	/// The identifier after the binary was stamped
+	pub new: Vec<u8>,
+}
+impl BuildIdUpdate {
+	/// Re-associates a separate debug file with the stamped binary, by replacing the old identifier in it with the new one.
+	///
+	/// Debuggers only load a debug file if its identifier matches the binary's, so debug files split from the binary before it was stamped,
+	/// such as `.debug` files produced by `objcopy --only-keep-debug` or the DWARF files inside a `.dSYM` bundle, must be updated too.
+	/// ELF debug files looked up by build ID should also be moved from [`BuildIdUpdate::old_debug_path`] to [`BuildIdUpdate::new_debug_path`].
+	///
+	/// Returns `false` without writing anything if the debug file doesn't contain the old identifier, such as when it belongs to a different binary.
+	///
+	/// ```no_run
+	/// let mut binary = linkstore::open_binary("app").unwrap();
+	/// let mut embedder = linkstore::Embedder::builder().update_build_id(true).build(&mut binary).unwrap();
+	/// embedder.embed("LINKSTORE_TEST", &69_u64).unwrap();
+	///
+	/// for update in embedder.finish_with_report().unwrap().build_ids {
+	///     let mut debug = linkstore::open_binary("app.debug").unwrap();
+	///     assert!(update.update_debug_file(&mut debug).unwrap());
+	/// }
+	/// ```
+	pub fn update_debug_file<'a, IO: BinaryHandle<'a>>(&self, handle: &'a mut IO) -> Result<bool, Error> {
+		let memory = handle.get_memory()?;
+		let bytes = match memory.as_deref() {
+			Some(bytes) => bytes,
+			None => handle.get_memory_ref().ok_or(Error::UnexpectedEof)?,
+		};
+
+		let object = formats::parse_object(bytes)?;
+		let offsets = find(bytes, &object)
+			.into_iter()
+			.filter(|id| id.kind == self.kind && id.arch == self.arch && bytes[id.id.clone()] == self.old[..])
+			.map(|id| id.id.start as u64)
+			.collect::<Vec<_>>();
+
+		for offset in offsets.iter() {
+			handle.seek(SeekFrom::Start(*offset))?;
+			handle.write_all(&self.new)?;
+		}
+
+		Ok(!offsets.is_empty())
+	}
+
+	/// The path, relative to a debug directory such as `/usr/lib/debug`, at which debuggers look up the ELF debug file for the binary before it was stamped.
+	///
+	/// Returns `None` for identifiers which aren't ELF build IDs. Mach-O debug files are found by their UUID instead, which [`BuildIdUpdate::update_debug_file`] updates.
+	pub fn old_debug_path(&self) -> Option<PathBuf> {
+		debug_path(self.kind, &self.old)
+	}
+
+	/// The path, relative to a debug directory such as `/usr/lib/debug`, at which debuggers look up the ELF debug file for the stamped binary.
+	///
+	/// Returns `None` for identifiers which aren't ELF build IDs. Mach-O debug files are found by their UUID instead, which [`BuildIdUpdate::update_debug_file`] updates.
+	pub fn new_debug_path(&self) -> Option<PathBuf> {
+		debug_path(self.kind, &self.new)
+	}
+}
+
+/// Returns `.build-id/xx/yyyy.debug`, where `xx` is the first byte of the build ID in hex and `yyyy` is the rest of it.
+fn debug_path(kind: BuildIdKind, id: &[u8]) -> Option<PathBuf> {
+	let (first, rest) = id.split_first().filter(|_| kind == BuildIdKind::GnuBuildId)?;
+	let rest = rest.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+	Some(PathBuf::from(".build-id").join(format!("{first:02x}")).join(format!("{rest}.debug")))
+}
+
+/// A build identifier found in a binary.
+pub(super) struct BuildId {
+	kind: BuildIdKind,
+	arch: Architecture,
+
+	/// The file offsets of the identifier
+	id: Range<usize>,
+
+	/// The file offsets of the object file it identifies, from which it is recomputed
+	object: Range<usize>,
+}
+
+/// Finds the build identifiers of an executable or shared library, and of each slice of a fat Mach-O binary.
+///
+/// Identifiers of object files inside static libraries are left alone.
+pub(super) fn find(bytes: &[u8], object: &goblin::Object) -> Vec<BuildId> {
+	match object {
+		goblin::Object::Elf(elf) => find_elf(bytes, elf),
+		goblin::Object::Mach(goblin::mach::Mach::Binary(macho)) => find_macho(macho, 0..bytes.len()),
+		goblin::Object::Mach(goblin::mach::Mach::Fat(fat)) => {
+			let mut ids = Vec::new();
+			for (i, arch) in fat.iter_arches().enumerate() {
+				let Ok(arch) = arch else {
+					break;
+				};
+				let start = arch.offset as usize;
+				let end = start.saturating_add(arch.size as usize).min(bytes.len());
+				if let Ok(goblin::mach::SingleArch::MachO(macho)) = fat.get(i) {
+					ids.extend(find_macho(&macho, start..end));
+				}
+			}
+			ids
+		}
+		_ => Vec::new(),
+	}
+}
+
+fn find_elf(bytes: &[u8], elf: &goblin::elf::Elf) -> Vec<BuildId> {
+	use goblin::elf::note::NT_GNU_BUILD_ID;
+
+	// Notes are found through the program headers of executables, and through the section headers of anything else
+	let notes = elf.iter_note_headers(bytes).or_else(|| elf.iter_note_sections(bytes, None));
+
+	let mut ids = Vec::<BuildId>::new();
+	for note in notes.into_iter().flatten().map_while(Result::ok) {
+		if note.n_type != NT_GNU_BUILD_ID || note.name != "GNU" || note.desc.is_empty() {
+			continue;
+		}
+
+		// The descriptor borrows from `bytes`, so its offset can be recovered from its address
+		let start = note.desc.as_ptr() as usize - bytes.as_ptr() as usize;
+		let id = start..start + note.desc.len();
+		if ids.iter().all(|other| other.id != id) {
+			ids.push(BuildId {
+				kind: BuildIdKind::GnuBuildId,
+				arch: Architecture::from_elf(elf.header.e_machine),
+				id,
+				object: 0..bytes.len(),
+			});
+		}
+	}
+	ids
+}
+
+fn find_macho(macho: &goblin::mach::MachO, object: Range<usize>) -> Vec<BuildId> {
+	use goblin::mach::load_command::{CommandVariant, SIZEOF_LOAD_COMMAND};
+
+	macho
+		.load_commands
+		.iter()
+		.filter_map(|command| match command.command {
+			CommandVariant::Uuid(ref uuid) => {
+				let start = object.start + command.offset + SIZEOF_LOAD_COMMAND;
+				Some(BuildId {
+					kind: BuildIdKind::MachOUuid,
+					arch: Architecture::from_mach(macho.header.cputype),
+					id: start..start + uuid.uuid.len(),
+					object: object.clone(),
+				})
+			}
+			_ => None,
+		})
+		.filter(|id| id.id.end <= object.end)
+		.collect()
+}
+
+/// Recomputes every build identifier from the stamped contents of the binary, writing them into `contents`.
+///
+/// Each identifier is a SHA-1 hash of the object file it identifies with every identifier zeroed, as linkers compute them, so it only depends on the rest of the contents,
+/// and stamping a binary with the same values always produces the same identifiers.
+pub(super) fn update(contents: &mut [u8], ids: &[BuildId]) -> Vec<BuildIdUpdate> {
+	let old = ids.iter().map(|id| contents[id.id.clone()].to_vec()).collect::<Vec<_>>();
+	for id in ids {
+		contents[id.id.clone()].fill(0);
+	}
+
+	let new = ids
+		.iter()
+		.map(|id| {
+			let mut new = digest(&contents[id.object.clone()], id.id.len());
+			if id.kind == BuildIdKind::MachOUuid {
+				// Marked as a name-based UUID, like the UUIDs generated by the linker
+				new[6] = (new[6] & 0x0F) | 0x30;
+				new[8] = (new[8] & 0x3F) | 0x80;
+			}
+			new
+		})
+		.collect::<Vec<_>>();
+
+	ids.iter()
+		.zip(old)
+		.zip(new)
+		.map(|((id, old), new)| {
+			contents[id.id.clone()].copy_from_slice(&new);
+			BuildIdUpdate {
+				kind: id.kind,
+				arch: id.arch,
+				offset: id.id.start as u64,
+				old,
+				new,
+			}
+		})
+		.collect()
+}
+
+/// Hashes `bytes` into `len` bytes with SHA-1, which is how linkers compute build identifiers by default.
+///
+/// Identifiers longer than a SHA-1 hash are extended with the hashes of `bytes` followed by the index of each further 20 bytes.
+fn digest(bytes: &[u8], len: usize) -> Vec<u8> {
+	let mut digest = Vec::with_capacity(len);
+	let mut index = 0_u64;
+	while digest.len() < len {
+		let mut sha1 = sha1_smol::Sha1::new();
+		sha1.update(bytes);
+		if index != 0 {
+			sha1.update(&index.to_le_bytes());
+		}
+		digest.extend(sha1.digest().bytes());
+		index += 1;
+	}
+	digest.truncate(len);
+	digest
+}
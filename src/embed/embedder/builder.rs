@@ -0,0 +1,130 @@
+use super::*;
+
+/// The section used by `linkstore!` unless another is specified.
+const DEFAULT_SECTION_NAME: &str = ".lnkstre";
+
+/// Configures and creates an [`Embedder`].
+///
+/// ## Example
+///
+/// ```no_run
+/// let mut binary: std::fs::File = linkstore::open_binary("C:\\Windows\\system32\\kernel32.dll").unwrap();
+///
+/// let mut embedder = linkstore::Embedder::builder()
+///     .strict(true)
+///     .section_name(".lnkstre")
+///     .section_name("__DATA,__mycfg")
+///     .build(&mut binary)
+///     .unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct EmbedderBuilder {
+	pub(crate) strict: bool,
+	pub(crate) section_names: Vec<String>,
+	pub(crate) discovery: DiscoveryMode,
+	pub(crate) tool: Option<String>,
+	pub(crate) update_build_id: bool,
+	pub(crate) signature_policy: SignaturePolicy,
+}
+impl EmbedderBuilder {
+	/// Creates a new [`EmbedderBuilder`] with the default options.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// In strict mode, creating the [`Embedder`] fails with [`Error::DivergentDuplicates`] if a key occurs more than once in the binary with differing sizes or values.
+	///
+	/// See [`Embedder::duplicates`] for more information about duplicate keys.
+	pub fn strict(mut self, strict: bool) -> Self {
+		self.strict = strict;
+		self
+	}
+
+	/// Adds a section name to search for linkstores in.
+	///
+	/// This can be called multiple times to search multiple sections. If no section names are added, the default `.lnkstre` section is searched.
+	///
+	/// Names of the form `segment,section` only match the section in that segment of a Mach-O binary, whilst plain names match the section in any segment.
+	///
+	/// PE images may truncate section names to 8 bytes, so longer names also match their truncated form.
+	///
+	/// Section names only apply when sections are scanned, as linkstores located by their symbols are found in any section. See [`DiscoveryMode`].
+	pub fn section_name(mut self, name: impl Into<String>) -> Self {
+		self.section_names.push(name.into());
+		self
+	}
+
+	/// Sets how linkstores are discovered in the binary. Defaults to [`DiscoveryMode::Auto`].
+	pub fn discovery(mut self, discovery: DiscoveryMode) -> Self {
+		self.discovery = discovery;
+		self
+	}
+
+	/// Sets the identifier of the tool stamping the binary, such as its name and version, which is recorded in the binary's provenance record.
+	///
+	/// Identifiers longer than [`Provenance::TOOL_LEN`](crate::Provenance::TOOL_LEN) bytes are truncated. See [`Embedder::provenance`].
+	pub fn tool(mut self, tool: impl Into<String>) -> Self {
+		self.tool = Some(tool.into());
+		self
+	}
+
+	/// Recomputes the binary's build identifiers after embedding, so that differently stamped binaries can be told apart by symbol servers, crash reporters and caches.
+	///
+	/// The `NT_GNU_BUILD_ID` note of an ELF binary and the `LC_UUID` of each Mach-O slice are replaced with a hash of the stamped binary,
+	/// so stamping the same binary with the same values always produces the same identifiers. [`Embedder::finish_with_report`] reports the old and new identifiers,
+	/// and [`BuildIdUpdate::update_debug_file`](crate::BuildIdUpdate::update_debug_file) re-associates separate debug files, such as `.debug` files and `.dSYM` bundles, with the stamped binary.
+	///
+	/// Debug links that don't depend on the binary's identifier, such as `.gnu_debuglink` sections and the CodeView records of PE images, are left untouched.
+	/// Binaries which nothing is written into keep their identifiers, as they are otherwise left untouched.
+	pub fn update_build_id(mut self, update_build_id: bool) -> Self {
+		self.update_build_id = update_build_id;
+		self
+	}
+
+	/// Sets what [`Embedder::finish`] does when it writes into a binary with a code signature. Defaults to [`SignaturePolicy::Warn`].
+	///
+	/// Writing into a signed binary invalidates its signature, so the binary will fail signature checks until it is signed again.
+	/// Signatures are detected when the [`Embedder`] is created, and can be listed with [`Embedder::signatures`].
+	pub fn signature_policy(mut self, signature_policy: SignaturePolicy) -> Self {
+		self.signature_policy = signature_policy;
+		self
+	}
+
+	pub(crate) fn section_names(&self) -> impl Iterator<Item = &str> {
+		let default = if self.section_names.is_empty() {
+			Some(DEFAULT_SECTION_NAME)
+		} else {
+			None
+		};
+		self.section_names.iter().map(String::as_str).chain(default)
+	}
+
+	/// Creates a new [`Embedder`] for a binary executable.
+	///
+	/// The handle must implement [`BinaryHandle`](crate::BinaryHandle)!
+	pub fn build<'a, IO: BinaryHandle<'a>>(self, handle: &'a mut IO) -> Result<Embedder<'a, IO>, Error> {
+		Embedder::with_options(handle, self)
+	}
+}
+
+/// How an [`Embedder`] discovers the linkstores in a binary.
+///
+/// Each static created by `linkstore!` has a symbol which locates it exactly, even if the linker has merged the linkstore section into another section.
+/// Stripped binaries have no symbol table, so their linkstore sections are also scanned for the magic bytes that begin each linkstore, checking each match against its header checksum.
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiscoveryMode {
+	/// Use the symbol table of each object file, and also scan its linkstore sections for linkstores whose symbols were stripped
+	///
+	/// Linkstores found both ways are only recorded once.
+	#[default]
+	Auto,
+
+	/// Only use symbol tables, finding no linkstores in stripped binaries
+	Symbols,
+
+	/// Only scan linkstore sections, ignoring symbol tables
+	Scan,
+}
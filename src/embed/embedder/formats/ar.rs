@@ -1,7 +1,71 @@
 use super::*;
 
+const AR_MAGIC: &[u8; 8] = b"!<arch>\n";
+const AR_HEADER_SIZE: usize = 60;
+
+/// Returns whether goblin can parse the archive without panicking.
+///
+/// goblin underflows when parsing an empty GNU name index, or a symbol index of zero in the second Windows linker member,
+/// so archives containing either are rejected before they reach goblin.
+pub(super) fn is_parsable(bytes: &[u8]) -> bool {
+	if !bytes.starts_with(AR_MAGIC) {
+		return true;
+	}
+
+	let mut linker_members = 0;
+	let mut offset = AR_MAGIC.len();
+	while let Some(header) = bytes.get(offset..).and_then(|bytes| bytes.get(..AR_HEADER_SIZE)) {
+		let size = match core::str::from_utf8(&header[48..58])
+			.ok()
+			.and_then(|size| size.trim_end().parse::<usize>().ok())
+		{
+			Some(size) => size,
+			None => return true,
+		};
+		let member = &bytes[offset + AR_HEADER_SIZE..];
+
+		if header.starts_with(b"// ") && size == 0 {
+			return false;
+		}
+
+		if header.starts_with(b"/ ") {
+			linker_members += 1;
+			if linker_members == 2 && !is_parsable_linker_member(member) {
+				return false;
+			}
+		}
+
+		// Members are aligned to 2 bytes
+		offset = match offset.checked_add(AR_HEADER_SIZE + size + (size & 1)) {
+			Some(offset) => offset,
+			None => return true,
+		};
+	}
+	true
+}
+
+/// The second Windows linker member is a list of member offsets, followed by a list of 1-based indices into it.
+fn is_parsable_linker_member(member: &[u8]) -> bool {
+	let u32_at = |offset: usize| {
+		member
+			.get(offset..)?
+			.get(..4)
+			.map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+	};
+
+	let Some(members) = u32_at(0) else { return true };
+	let Some(symbols_offset) = members.checked_mul(4).and_then(|size| size.checked_add(4)) else {
+		return true;
+	};
+	let Some(symbols) = u32_at(symbols_offset) else { return true };
+
+	(0..symbols)
+		.map_while(|i| member.get(symbols_offset + 4 + i * 2..)?.get(..2))
+		.all(|index| index != [0, 0])
+}
+
 pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
-	all_embeds: &mut Linkstores,
+	discovery: &mut Discovery,
 	handle: &mut BufReader<Cursor<&[u8]>>,
 	ar: &goblin::archive::Archive,
 	ar_offset: u64,
@@ -10,17 +74,22 @@ pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
 
 	let mut i = 0;
 	while i < ar.len() {
-		let bin = ar.get_at(i).ok_or(Error::DecodingError)?;
+		let member = ar.get_at(i).ok_or(Error::DecodingError)?;
 
-		let offset = ar_offset + bin.offset;
-		let bin = &handle[offset as usize..][..bin.size() as usize];
+		// Member offsets are relative to the archive, which may itself be a member of a fat binary
+		let bin = usize::try_from(member.offset)
+			.ok()
+			.and_then(|offset| handle.get(offset..)?.get(..member.size()))
+			.ok_or(Error::UnexpectedEof)?;
+		let offset = ar_offset + member.offset;
 
 		super::discover_linkstores::<IO>(
+			discovery,
 			bin,
-			&goblin::Object::parse(bin)?,
+			&super::parse_object(bin)?,
 			&mut BufReader::new(Cursor::new(bin)),
-			all_embeds,
 			offset,
+			Some(member.extended_name()),
 		)?;
 
 		i += 1;
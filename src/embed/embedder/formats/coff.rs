@@ -1,17 +1,94 @@
 use super::*;
+use goblin::{
+	pe::{
+		header::CoffHeader,
+		section_table::SectionTable,
+		symbol::{SymbolTable, COFF_SYMBOL_SIZE},
+	},
+	strtab::Strtab,
+};
+
+/// Returns whether the string table following the COFF symbol table can be parsed.
+///
+/// goblin subtracts the size of the length field from the string table's length without checking it, which overflows on malformed binaries.
+pub(super) fn has_valid_string_table(header: &CoffHeader, bytes: &[u8]) -> bool {
+	(header.number_of_symbol_table as usize)
+		.checked_mul(COFF_SYMBOL_SIZE)
+		.and_then(|size| size.checked_add(header.pointer_to_symbol_table as usize))
+		.and_then(|offset| bytes.get(offset..)?.get(..4))
+		.is_some_and(|length| u32::from_le_bytes(length.try_into().unwrap()) >= 4)
+}
+
+/// Finds the linkstore symbols in a COFF symbol table, which is present in object files and some PE images.
+pub(super) fn container_symbols(symbols: &SymbolTable, strings: &Strtab, sections: &[SectionTable]) -> Vec<ContainerSymbol> {
+	symbols
+		.iter()
+		// goblin subtracts the size of the length field from long name offsets without checking them either
+		.filter(|(_, _, symbol)| symbol.name[0] != 0 || u32::from_le_bytes(symbol.name[4..].try_into().unwrap()) >= 4)
+		.filter(|(_, _, symbol)| symbol.name(strings).is_ok_and(Discovery::is_container_symbol))
+		.filter_map(|(_, _, symbol)| {
+			// Section numbers are 1-based, and values are offsets from the start of the section
+			let section = usize::try_from(symbol.section_number).ok()?.checked_sub(1)?;
+			let offset = symbol.value as u64;
+			(offset < sections.get(section)?.size_of_raw_data as u64).then_some(ContainerSymbol { section, offset })
+		})
+		.collect()
+}
 
 pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
-	embeds: &mut Linkstores,
+	discovery: &mut Discovery,
 	handle: &mut BufReader<Cursor<&[u8]>>,
 	coff: &goblin::pe::Coff,
 	ar_offset: u64,
+	member: Option<&str>,
 ) -> Result<(), Error> {
-	for header in coff
-		.sections
-		.iter()
-		.filter_map(|section| filter_map_linkstore_section(&section.name, section))
-	{
-		Embedder::<IO>::decode_section(embeds, handle, header.pointer_to_raw_data as _, header.size_of_raw_data as _, ar_offset)?;
+	let location = Location {
+		arch: Architecture::from_coff(coff.header.machine),
+		member: member.map(str::to_owned),
+		offset: ar_offset,
+		..Location::new(Format::Coff, Architecture::coff_pointer_width(coff.header.machine))
+	};
+	let section_location = |name: &str| Location {
+		section: name.to_owned(),
+		..location.clone()
+	};
+
+	let symbols = if discovery.use_symbols() {
+		container_symbols(&coff.symbols, &coff.strings, &coff.sections)
+	} else {
+		Vec::new()
+	};
+
+	for symbol in symbols.iter() {
+		let header = &coff.sections[symbol.section];
+		Embedder::<IO>::decode_symbol(
+			discovery.embeds,
+			handle,
+			header.pointer_to_raw_data as _,
+			header.pointer_to_raw_data as u64 + symbol.offset,
+			header.size_of_raw_data as u64 - symbol.offset,
+			&section_location(header.name().unwrap_or_default()),
+		);
+	}
+
+	if !discovery.use_scan() {
+		return Ok(());
+	}
+
+	for header in coff.sections.iter() {
+		// Long section names in object files are stored in the string table
+		let name = match header.name() {
+			Ok(name) if discovery.is_linkstore_section(None, name, None) => name,
+			_ => continue,
+		};
+
+		Embedder::<IO>::decode_section(
+			discovery.embeds,
+			handle,
+			header.pointer_to_raw_data as _,
+			header.size_of_raw_data as _,
+			&section_location(name),
+		)?;
 	}
 	Ok(())
 }
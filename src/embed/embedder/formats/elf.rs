@@ -1,17 +1,81 @@
 use super::*;
 
 pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
-	embeds: &mut Linkstores,
+	discovery: &mut Discovery,
 	handle: &mut BufReader<Cursor<&[u8]>>,
 	elf: &goblin::elf::Elf,
 	ar_offset: u64,
+	member: Option<&str>,
 ) -> Result<(), Error> {
-	for header in elf
-		.section_headers
-		.iter()
-		.filter_map(|section| filter_map_linkstore_section(elf.shdr_strtab.get_at(section.sh_name)?.as_bytes(), section))
-	{
-		Embedder::<IO>::decode_section(embeds, handle, header.sh_offset, header.sh_size, ar_offset)?;
+	use goblin::elf::{header::ET_REL, section_header::SHT_NOBITS, sym::STT_SECTION};
+
+	let location = Location {
+		arch: Architecture::from_elf(elf.header.e_machine),
+		member: member.map(str::to_owned),
+		offset: ar_offset,
+		..Location::new(Format::Elf, PointerWidth::from_is_64(elf.is_64))
+	};
+	let section_location = |header: &goblin::elf::SectionHeader, name: &str| Location {
+		section: name.to_owned(),
+		virtual_address: if elf.header.e_type == ET_REL { None } else { Some(header.sh_addr) },
+		..location.clone()
+	};
+
+	let symbols = if discovery.use_symbols() {
+		elf.syms
+			.iter()
+			.filter(|sym| sym.st_type() != STT_SECTION && elf.strtab.get_at(sym.st_name).is_some_and(Discovery::is_container_symbol))
+			.filter_map(|sym| {
+				let header = elf.section_headers.get(sym.st_shndx).filter(|header| header.sh_type != SHT_NOBITS)?;
+
+				// Symbol values are section offsets in relocatable objects, and virtual addresses otherwise
+				let offset = if elf.header.e_type == ET_REL {
+					sym.st_value
+				} else {
+					sym.st_value.checked_sub(header.sh_addr)?
+				};
+
+				(offset < header.sh_size).then_some(ContainerSymbol {
+					section: sym.st_shndx,
+					offset,
+				})
+			})
+			.collect()
+	} else {
+		Vec::new()
+	};
+
+	for symbol in symbols.iter() {
+		let header = &elf.section_headers[symbol.section];
+		let name = elf.shdr_strtab.get_at(header.sh_name).unwrap_or_default();
+
+		Embedder::<IO>::decode_symbol(
+			discovery.embeds,
+			handle,
+			header.sh_offset,
+			header.sh_offset.saturating_add(symbol.offset),
+			header.sh_size - symbol.offset,
+			&section_location(header, name),
+		);
+	}
+
+	if !discovery.use_scan() {
+		return Ok(());
+	}
+
+	for header in elf.section_headers.iter() {
+		let name = match elf.shdr_strtab.get_at(header.sh_name) {
+			Some(name) if discovery.is_linkstore_section(None, name, None) => name,
+			_ => continue,
+		};
+
+		Embedder::<IO>::decode_section(
+			discovery.embeds,
+			handle,
+			header.sh_offset,
+			header.sh_size,
+			&section_location(header, name),
+		)?;
 	}
 	Ok(())
 }
@@ -0,0 +1,90 @@
+//! Entry points for the fuzz targets in `fuzz/`, which drive each format backend directly.
+//!
+//! Only compiled with `--cfg fuzzing`, which `cargo fuzz` sets.
+
+use super::*;
+
+type Backend<'b> = dyn FnMut(&mut Discovery, &mut BufReader<Cursor<&[u8]>>) -> Result<(), Error> + 'b;
+
+/// Runs a backend over the bytes once with each [`DiscoveryMode`] that exercises a different path.
+fn discover(bytes: &[u8], backend: &mut Backend) {
+	for discovery in [DiscoveryMode::Symbols, DiscoveryMode::Scan] {
+		let options = EmbedderBuilder::new().discovery(discovery);
+		let mut embeds = Linkstores::new();
+		let _ = backend(
+			&mut Discovery {
+				options: &options,
+				embeds: &mut embeds,
+			},
+			&mut BufReader::new(Cursor::new(bytes)),
+		);
+	}
+}
+
+/// Scans the bytes for linkstores as if they were a linkstore section.
+pub fn decode_section(bytes: &[u8], pointer_width: PointerWidth) {
+	let location = Location::new(Format::Elf, pointer_width);
+	let _ = Embedder::<std::fs::File>::decode_section(
+		&mut Linkstores::new(),
+		&mut BufReader::new(Cursor::new(bytes)),
+		0,
+		bytes.len() as u64,
+		&location,
+	);
+}
+
+pub fn elf(bytes: &[u8]) {
+	if let Ok(elf) = goblin::elf::Elf::parse(bytes) {
+		discover(bytes, &mut |discovery, handle| {
+			elf::discover_linkstores::<std::fs::File>(discovery, handle, &elf, 0, None)
+		});
+	}
+}
+
+pub fn pe(bytes: &[u8]) {
+	if let Ok(pe) = goblin::pe::PE::parse(bytes) {
+		discover(bytes, &mut |discovery, handle| {
+			pe::discover_linkstores::<std::fs::File>(discovery, handle, &pe, 0, None)
+		});
+	}
+}
+
+pub fn coff(bytes: &[u8]) {
+	if !goblin::pe::header::CoffHeader::parse(bytes, &mut 0).is_ok_and(|header| coff::has_valid_string_table(&header, bytes)) {
+		return;
+	}
+	if let Ok(coff) = goblin::pe::Coff::parse(bytes) {
+		discover(bytes, &mut |discovery, handle| {
+			coff::discover_linkstores::<std::fs::File>(discovery, handle, &coff, 0, None)
+		});
+	}
+}
+
+pub fn macho(bytes: &[u8]) {
+	match goblin::mach::Mach::parse(bytes) {
+		Ok(goblin::mach::Mach::Binary(macho)) => discover(bytes, &mut |discovery, handle| {
+			macho::discover_linkstores::<std::fs::File>(discovery, handle, &macho, 0, None)
+		}),
+		Ok(goblin::mach::Mach::Fat(fat)) => discover(bytes, &mut |discovery, handle| {
+			macho::discover_linkstores_multiarch::<std::fs::File>(discovery, handle, &fat)
+		}),
+		Err(_) => {}
+	}
+}
+
+pub fn ar(bytes: &[u8]) {
+	if !ar::is_parsable(bytes) {
+		return;
+	}
+	if let Ok(ar) = goblin::archive::Archive::parse(bytes) {
+		discover(bytes, &mut |discovery, handle| {
+			ar::discover_linkstores::<std::fs::File>(discovery, handle, &ar, 0)
+		});
+	}
+}
+
+pub fn wasm(bytes: &[u8]) {
+	discover(bytes, &mut |discovery, handle| {
+		wasm::discover_linkstores::<std::fs::File>(discovery, handle, bytes, 0, None)
+	});
+}
@@ -1,34 +1,105 @@
 use super::*;
 
 pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
-	embeds: &mut Linkstores,
+	discovery: &mut Discovery,
 	handle: &mut BufReader<Cursor<&[u8]>>,
 	macho: &goblin::mach::MachO,
 	fat_offset: u64,
+	member: Option<&str>,
 ) -> Result<(), Error> {
+	use goblin::mach::{
+		header::MH_OBJECT,
+		symbols::{N_SECT, N_STAB, N_TYPE},
+	};
+
+	let location = Location {
+		arch: Architecture::from_mach(macho.header.cputype),
+		member: member.map(str::to_owned),
+		offset: fat_offset,
+		..Location::new(Format::MachO, PointerWidth::from_is_64(macho.is_64))
+	};
+	let section_location = |section: &goblin::mach::segment::Section, segname: &str, name: &str| Location {
+		section: name.to_owned(),
+		segment: Some(segname.to_owned()),
+		virtual_address: if macho.header.filetype == MH_OBJECT { None } else { Some(section.addr) },
+		..location.clone()
+	};
+
+	// Symbols refer to sections by their 1-based ordinal across every segment
+	let mut sections = Vec::new();
 	for segment in macho.segments.iter() {
-		for section in segment
-			.sections()?
-			.iter()
-			.filter_map(|(section, _)| filter_map_linkstore_section(section.name().ok()?.as_bytes(), section))
-		{
-			Embedder::<IO>::decode_section(embeds, handle, section.offset as u64, section.size, fat_offset)?;
-		}
+		sections.extend(segment.sections()?.into_iter().map(|(section, _)| section));
+	}
+
+	let symbols = if discovery.use_symbols() {
+		macho
+			.symbols()
+			// The symbol count is untrusted, so stop at the first symbol which can't be read
+			.map_while(Result::ok)
+			.filter(|(name, nlist)| nlist.n_type & N_STAB == 0 && nlist.n_type & N_TYPE == N_SECT && Discovery::is_container_symbol(name))
+			.filter_map(|(_, nlist)| {
+				let section = nlist.n_sect.checked_sub(1)?;
+				let offset = nlist.n_value.checked_sub(sections.get(section)?.addr)?;
+				(offset < sections[section].size).then_some(ContainerSymbol { section, offset })
+			})
+			.collect()
+	} else {
+		Vec::new()
+	};
+
+	for symbol in symbols.iter() {
+		let section = &sections[symbol.section];
+		Embedder::<IO>::decode_symbol(
+			discovery.embeds,
+			handle,
+			section.offset as u64,
+			(section.offset as u64).saturating_add(symbol.offset),
+			section.size - symbol.offset,
+			&section_location(section, section.segname().unwrap_or_default(), section.name().unwrap_or_default()),
+		);
+	}
+
+	if !discovery.use_scan() {
+		return Ok(());
+	}
+
+	for section in sections.iter() {
+		let (segname, name) = match (section.segname(), section.name()) {
+			(Ok(segname), Ok(name)) if discovery.is_linkstore_section(Some(segname), name, None) => (segname, name),
+			_ => continue,
+		};
+
+		Embedder::<IO>::decode_section(
+			discovery.embeds,
+			handle,
+			section.offset as u64,
+			section.size,
+			&section_location(section, segname, name),
+		)?;
 	}
 	Ok(())
 }
 
 pub(super) fn discover_linkstores_multiarch<'a, IO: BinaryHandle<'a> + 'a>(
-	embeds: &mut Linkstores,
+	discovery: &mut Discovery,
 	handle: &mut BufReader<Cursor<&[u8]>>,
 	multiarch: &goblin::mach::MultiArch,
 ) -> Result<(), Error> {
+	let bytes = *handle.get_ref().get_ref();
 	for (i, arch) in multiarch.iter_arches().enumerate() {
-		let offset = arch?.offset as u64;
-		let arch = multiarch.get(i)?;
-		match arch {
-			goblin::mach::SingleArch::MachO(macho) => discover_linkstores::<IO>(embeds, handle, &macho, offset)?,
-			goblin::mach::SingleArch::Archive(ar) => super::ar::discover_linkstores::<IO>(embeds, handle, &ar, offset)?,
+		let arch = arch?;
+		let offset = arch.offset as u64;
+
+		// Section offsets are relative to the start of the slice
+		let slice = bytes
+			.get(arch.offset as usize..)
+			.and_then(|slice| slice.get(..arch.size as usize))
+			.ok_or(Error::UnexpectedEof)?;
+		let handle = &mut BufReader::new(Cursor::new(slice));
+
+		match multiarch.get(i)? {
+			goblin::mach::SingleArch::MachO(macho) => discover_linkstores::<IO>(discovery, handle, &macho, offset, None)?,
+			goblin::mach::SingleArch::Archive(ar) => super::ar::discover_linkstores::<IO>(discovery, handle, &ar, offset)?,
 		}
 	}
 	Ok(())
@@ -5,34 +5,98 @@ mod coff;
 mod elf;
 pub mod macho;
 mod pe;
+mod wasm;
 
-fn filter_map_linkstore_section<'a, T>(name: &'a [u8], section: &'a T) -> Option<&'a T> {
-	if name == b".lnkstre" {
-		Some(section)
-	} else {
-		None
+#[cfg(fuzzing)]
+pub mod fuzz;
+
+/// The name of the static which `linkstore!` generates for each linkstore, which appears in its mangled symbol name.
+const CONTAINER_SYMBOL: &str = "__LINKSTORE_CONTAINER";
+
+/// A linkstore located by its symbol.
+struct ContainerSymbol {
+	/// The index of the section containing the linkstore
+	section: usize,
+
+	/// The offset of the linkstore from the start of its section
+	offset: u64,
+}
+
+/// State shared by every format backend whilst discovering linkstores.
+pub(super) struct Discovery<'d, 'a> {
+	pub(super) options: &'d EmbedderBuilder,
+	pub(super) embeds: &'d mut Linkstores<'a>,
+}
+impl Discovery<'_, '_> {
+	/// Returns whether symbol tables should be searched for linkstores.
+	fn use_symbols(&self) -> bool {
+		self.options.discovery != DiscoveryMode::Scan
+	}
+
+	/// Returns whether linkstore sections should be scanned for linkstores.
+	///
+	/// Linkstores which were also located by their symbols are only recorded once. See [`Embedder::decode_container`].
+	fn use_scan(&self) -> bool {
+		self.options.discovery != DiscoveryMode::Symbols
+	}
+
+	fn is_container_symbol(name: &str) -> bool {
+		name.contains(CONTAINER_SYMBOL)
+	}
+
+	/// Returns whether a section should be searched for linkstores.
+	///
+	/// Section names of the form `segment,section` only match sections in that segment.
+	///
+	/// If the format truncates long section names, `max_len` is the maximum length of a section name.
+	fn is_linkstore_section(&self, segment: Option<&str>, name: &str, max_len: Option<usize>) -> bool {
+		self.options.section_names().any(|section_name| {
+			let section_name = match section_name.split_once(',') {
+				Some((segname, sectname)) if segment == Some(segname) => sectname,
+				Some(_) => return false,
+				None => section_name,
+			};
+			match max_len {
+				Some(max_len) if name.len() == max_len => section_name.starts_with(name),
+				_ => section_name == name,
+			}
+		})
 	}
 }
 
+/// Parses an object file with goblin, first rejecting malformed binaries which goblin would panic on.
+pub(super) fn parse_object(bytes: &[u8]) -> Result<goblin::Object<'_>, Error> {
+	if !ar::is_parsable(bytes) {
+		return Err(Error::DecodingError);
+	}
+	Ok(goblin::Object::parse(bytes)?)
+}
+
 pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
+	discovery: &mut Discovery,
 	bytes: &[u8],
 	object: &goblin::Object,
 	handle: &mut BufReader<Cursor<&[u8]>>,
-	embeds: &mut Linkstores,
 	ar_offset: u64,
+	member: Option<&str>,
 ) -> Result<(), Error> {
 	match object {
-		goblin::Object::Elf(elf) => elf::discover_linkstores::<IO>(embeds, handle, elf, ar_offset),
-		goblin::Object::PE(pe) => pe::discover_linkstores::<IO>(embeds, handle, pe, ar_offset),
-		goblin::Object::Archive(ar) => ar::discover_linkstores::<IO>(embeds, handle, ar, ar_offset),
+		goblin::Object::Elf(elf) => elf::discover_linkstores::<IO>(discovery, handle, elf, ar_offset, member),
+		goblin::Object::PE(pe) => pe::discover_linkstores::<IO>(discovery, handle, pe, ar_offset, member),
+		goblin::Object::Archive(ar) => ar::discover_linkstores::<IO>(discovery, handle, ar, ar_offset),
 
-		goblin::Object::Mach(goblin::mach::Mach::Binary(macho)) => macho::discover_linkstores::<IO>(embeds, handle, macho, ar_offset),
-		goblin::Object::Mach(goblin::mach::Mach::Fat(fat)) => macho::discover_linkstores_multiarch::<IO>(embeds, handle, fat),
+		goblin::Object::Mach(goblin::mach::Mach::Binary(macho)) => macho::discover_linkstores::<IO>(discovery, handle, macho, ar_offset, member),
+		goblin::Object::Mach(goblin::mach::Mach::Fat(fat)) => macho::discover_linkstores_multiarch::<IO>(discovery, handle, fat),
 
 		goblin::Object::Unknown(_) => {
-			if ar_offset != 0 {
+			if wasm::is_wasm(bytes) {
+				return wasm::discover_linkstores::<IO>(discovery, handle, bytes, ar_offset, member);
+			}
+			if member.is_some()
+				&& goblin::pe::header::CoffHeader::parse(bytes, &mut 0).is_ok_and(|header| coff::has_valid_string_table(&header, bytes))
+			{
 				if let Ok(coff) = goblin::pe::Coff::parse(bytes) {
-					return coff::discover_linkstores::<IO>(embeds, handle, &coff, ar_offset);
+					return coff::discover_linkstores::<IO>(discovery, handle, &coff, ar_offset, member);
 				}
 			}
 			Err(Error::Unrecognised)
@@ -1,16 +1,68 @@
 use super::*;
+
+const SECTION_NAME_LEN: usize = 8;
+
 pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
-	embeds: &mut Linkstores,
+	discovery: &mut Discovery,
 	handle: &mut BufReader<Cursor<&[u8]>>,
 	pe: &goblin::pe::PE,
 	ar_offset: u64,
+	member: Option<&str>,
 ) -> Result<(), Error> {
-	for header in pe
-		.sections
-		.iter()
-		.filter_map(|section| filter_map_linkstore_section(&section.name, section))
-	{
-		Embedder::<IO>::decode_section(embeds, handle, header.pointer_to_raw_data as _, header.virtual_size as _, ar_offset)?;
+	let location = Location {
+		arch: Architecture::from_coff(pe.header.coff_header.machine),
+		member: member.map(str::to_owned),
+		offset: ar_offset,
+		..Location::new(Format::Pe, PointerWidth::from_is_64(pe.is_64))
+	};
+	let section_location = |header: &goblin::pe::section_table::SectionTable, name: &str| Location {
+		section: name.to_owned(),
+		virtual_address: Some((pe.image_base as u64).wrapping_add(header.virtual_address as u64)),
+		..location.clone()
+	};
+
+	// Images linked by MSVC keep their symbols in a separate PDB, but MinGW images may still have a COFF symbol table
+	let coff_header = &pe.header.coff_header;
+	let bytes = *handle.get_ref().get_ref();
+	let symbols = if discovery.use_symbols() && coff_header.pointer_to_symbol_table != 0 && super::coff::has_valid_string_table(coff_header, bytes) {
+		match (coff_header.symbols(bytes), coff_header.strings(bytes)) {
+			(Ok(symbols), Ok(strings)) => super::coff::container_symbols(&symbols, &strings, &pe.sections),
+			_ => Vec::new(),
+		}
+	} else {
+		Vec::new()
+	};
+
+	for symbol in symbols.iter() {
+		let header = &pe.sections[symbol.section];
+		Embedder::<IO>::decode_symbol(
+			discovery.embeds,
+			handle,
+			header.pointer_to_raw_data as _,
+			header.pointer_to_raw_data as u64 + symbol.offset,
+			header.size_of_raw_data as u64 - symbol.offset,
+			&section_location(header, header.name().unwrap_or_default()),
+		);
+	}
+
+	if !discovery.use_scan() {
+		return Ok(());
+	}
+
+	for header in pe.sections.iter() {
+		// Section names in images are truncated to 8 bytes, unless the linker stored the full name in the string table
+		let name = match header.name() {
+			Ok(name) if discovery.is_linkstore_section(None, name, Some(SECTION_NAME_LEN)) => name,
+			_ => continue,
+		};
+
+		Embedder::<IO>::decode_section(
+			discovery.embeds,
+			handle,
+			header.pointer_to_raw_data as _,
+			header.virtual_size as _,
+			&section_location(header, name),
+		)?;
 	}
 	Ok(())
 }
@@ -0,0 +1,170 @@
+use super::*;
+
+const WASM_MAGIC: &[u8; 4] = b"\0asm";
+const WASM_VERSION: &[u8; 4] = &[1, 0, 0, 0];
+
+const SECTION_CUSTOM: u8 = 0;
+const SECTION_MEMORY: u8 = 5;
+const SECTION_DATA: u8 = 11;
+
+const LIMITS_MEMORY64: u32 = 0x04;
+
+const OP_I32_CONST: u8 = 0x41;
+const OP_I64_CONST: u8 = 0x42;
+const OP_END: u8 = 0x0B;
+
+pub(super) fn is_wasm(bytes: &[u8]) -> bool {
+	bytes.starts_with(WASM_MAGIC)
+}
+
+/// A bounds-checked cursor over the sections of a WebAssembly module.
+struct WasmReader<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+}
+impl<'a> WasmReader<'a> {
+	fn u8(&mut self) -> Result<u8, Error> {
+		let byte = *self.bytes.get(self.pos).ok_or(Error::UnexpectedEof)?;
+		self.pos += 1;
+		Ok(byte)
+	}
+
+	fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+		let bytes = self
+			.pos
+			.checked_add(len)
+			.and_then(|end| self.bytes.get(self.pos..end))
+			.ok_or(Error::UnexpectedEof)?;
+		self.pos += len;
+		Ok(bytes)
+	}
+
+	fn leb128(&mut self, signed: bool) -> Result<u64, Error> {
+		let mut result = 0_u64;
+		let mut shift = 0;
+		loop {
+			let byte = self.u8()?;
+			if shift >= 64 {
+				return Err(Error::DecodingError);
+			}
+			result |= ((byte & 0x7F) as u64) << shift;
+			shift += 7;
+			if byte & 0x80 == 0 {
+				if signed && shift < 64 && byte & 0x40 != 0 {
+					result |= !0 << shift;
+				}
+				return Ok(result);
+			}
+		}
+	}
+
+	fn u32(&mut self) -> Result<u32, Error> {
+		u32::try_from(self.leb128(false)?).map_err(|_| Error::DecodingError)
+	}
+
+	fn name(&mut self) -> Result<&'a str, Error> {
+		let len = self.u32()? as usize;
+		core::str::from_utf8(self.bytes(len)?).map_err(|_| Error::NameDecodingError)
+	}
+
+	/// Reads a constant expression, returning its value if it is a plain integer constant.
+	fn const_expr(&mut self) -> Result<Option<u64>, Error> {
+		let value = match self.u8()? {
+			OP_I32_CONST => Some(self.leb128(true)? as u32 as u64),
+			OP_I64_CONST => Some(self.leb128(true)?),
+			_ => None,
+		};
+		while self.u8()? != OP_END {}
+		Ok(value)
+	}
+}
+
+/// WebAssembly modules have no linkstore section of their own, because static variables with a `link_section` are emitted as custom sections which can't be read at runtime.
+///
+/// Instead, `linkstore!` leaves them in the module's data segments, which are scanned for linkstores. Custom sections with the linkstore section name are also supported.
+pub(super) fn discover_linkstores<'a, IO: BinaryHandle<'a> + 'a>(
+	discovery: &mut Discovery,
+	handle: &mut BufReader<Cursor<&[u8]>>,
+	bytes: &[u8],
+	ar_offset: u64,
+	member: Option<&str>,
+) -> Result<(), Error> {
+	let mut wasm = WasmReader { bytes, pos: 0 };
+	if wasm.bytes(4)? != WASM_MAGIC || wasm.bytes(4)? != WASM_VERSION {
+		return Err(Error::Unrecognised);
+	}
+
+	let mut location = Location {
+		arch: Architecture::Wasm32,
+		member: member.map(str::to_owned),
+		offset: ar_offset,
+		..Location::new(Format::Wasm, PointerWidth::Bits32)
+	};
+
+	while wasm.pos < bytes.len() {
+		let id = wasm.u8()?;
+		let size = wasm.u32()? as usize;
+		let end = wasm.pos.checked_add(size).filter(|end| *end <= bytes.len()).ok_or(Error::UnexpectedEof)?;
+
+		match id {
+			SECTION_CUSTOM => {
+				let name = wasm.name()?;
+				if wasm.pos > end {
+					return Err(Error::UnexpectedEof);
+				}
+				if discovery.is_linkstore_section(None, name, None) {
+					let location = Location {
+						section: name.to_owned(),
+						..location.clone()
+					};
+					Embedder::<IO>::decode_section(discovery.embeds, handle, wasm.pos as u64, (end - wasm.pos) as u64, &location)?;
+				}
+			}
+
+			SECTION_MEMORY => {
+				// The data section always follows the memory section
+				let memory64 = wasm.u32()? > 0 && wasm.u32()? & LIMITS_MEMORY64 != 0;
+				if memory64 {
+					location.arch = Architecture::Wasm64;
+					location.pointer_width = PointerWidth::Bits64;
+				}
+			}
+
+			SECTION_DATA => {
+				for i in 0..wasm.u32()? {
+					let address = match wasm.u32()? {
+						// Active segment in memory 0
+						0 => wasm.const_expr()?,
+
+						// Passive segment
+						1 => None,
+
+						// Active segment with explicit memory index
+						2 => {
+							wasm.u32()?;
+							wasm.const_expr()?
+						}
+
+						_ => return Err(Error::DecodingError),
+					};
+
+					let len = wasm.u32()? as usize;
+					let location = Location {
+						section: format!("data[{i}]"),
+						virtual_address: address,
+						..location.clone()
+					};
+					Embedder::<IO>::decode_section(discovery.embeds, handle, wasm.pos as u64, len as u64, &location)?;
+
+					wasm.bytes(len)?;
+				}
+			}
+
+			_ => {}
+		}
+
+		wasm.pos = end;
+	}
+
+	Ok(())
+}
@@ -31,13 +31,13 @@ where
 	type Item = Result<T, <T as TryDecodeLinkstore>::Error>;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let embed = match self.embeds.get(self.idx) {
-			Some(embed) => embed,
-			None => return None,
-		};
+		let embed = self.embeds.get(self.idx)?;
 		self.idx += 1;
 
-		Some(TryDecodeLinkstore::try_from_le_bytes(embed.bytes.as_ref()))
+		Some(TryDecodeLinkstore::try_from_target_le_bytes(
+			embed.bytes.as_ref(),
+			embed.location.pointer_width.bytes(),
+		))
 	}
 }
 
@@ -68,12 +68,12 @@ where
 	type Item = T;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let embed = match self.embeds.get(self.idx) {
-			Some(embed) => embed,
-			None => return None,
-		};
+		let embed = self.embeds.get(self.idx)?;
 		self.idx += 1;
 
-		Some(DecodeLinkstore::from_le_bytes(embed.bytes.as_ref()))
+		Some(DecodeLinkstore::from_target_le_bytes(
+			embed.bytes.as_ref(),
+			embed.location.pointer_width.bytes(),
+		))
 	}
 }
@@ -0,0 +1,186 @@
+/// Where an occurrence of a linkstore was discovered in a binary.
+///
+/// Returned by [`Embedder::locations`](crate::Embedder::locations), and used to select which occurrences of a key are embedded into with [`Embedder::embed_where`](crate::Embedder::embed_where).
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+	/// The format of the object file containing the linkstore
+	pub format: Format,
+
+	/// The CPU architecture of the object file containing the linkstore
+	pub arch: Architecture,
+
+	/// The pointer width of the object file containing the linkstore
+	pub pointer_width: PointerWidth,
+
+	/// The name of the archive member containing the linkstore, if the binary is a static library
+	pub member: Option<String>,
+
+	/// The name of the section containing the linkstore
+	pub section: String,
+
+	/// The name of the segment containing the section, for Mach-O binaries
+	pub segment: Option<String>,
+
+	/// The file offset of the linkstore's value
+	pub offset: u64,
+
+	/// The virtual address of the linkstore's value when the binary is loaded at its preferred base address
+	///
+	/// This is `None` for relocatable object files, such as archive members, which are not yet assigned addresses.
+	pub virtual_address: Option<u64>,
+}
+impl Location {
+	pub(crate) fn new(format: Format, pointer_width: PointerWidth) -> Self {
+		Self {
+			format,
+			arch: Architecture::Unknown,
+			pointer_width,
+			member: None,
+			section: String::new(),
+			segment: None,
+			offset: 0,
+			virtual_address: None,
+		}
+	}
+
+	/// Returns whether the object file was built for the same target as the embedder, so that types are laid out as they are on the host.
+	pub(crate) fn is_host(&self) -> bool {
+		let format = if cfg!(target_family = "wasm") {
+			matches!(self.format, Format::Wasm)
+		} else if cfg!(windows) {
+			matches!(self.format, Format::Pe | Format::Coff)
+		} else if cfg!(target_vendor = "apple") {
+			matches!(self.format, Format::MachO)
+		} else {
+			matches!(self.format, Format::Elf)
+		};
+
+		let arch = match self.arch {
+			Architecture::X86 => cfg!(target_arch = "x86"),
+			Architecture::X86_64 => cfg!(target_arch = "x86_64"),
+			Architecture::Arm => cfg!(target_arch = "arm"),
+			Architecture::Aarch64 => cfg!(target_arch = "aarch64"),
+			Architecture::PowerPc => cfg!(target_arch = "powerpc"),
+			Architecture::PowerPc64 => cfg!(target_arch = "powerpc64"),
+			Architecture::Wasm32 => cfg!(target_arch = "wasm32"),
+			Architecture::Wasm64 => cfg!(target_arch = "wasm64"),
+			Architecture::Unknown => false,
+		};
+
+		format && arch && self.pointer_width.bytes() == core::mem::size_of::<usize>()
+	}
+}
+
+/// The format of an object file containing a linkstore.
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+	/// Executable and Linkable Format
+	Elf,
+
+	/// Portable Executable
+	Pe,
+
+	/// Common Object File Format, found in MSVC static libraries
+	Coff,
+
+	/// Mach-O
+	MachO,
+
+	/// WebAssembly module
+	Wasm,
+}
+
+/// The pointer width of an object file containing a linkstore.
+///
+/// Linkstore headers store sizes as the target's `usize`, so this determines the layout of the header.
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerWidth {
+	/// 32-bit pointers
+	Bits32,
+
+	/// 64-bit pointers
+	Bits64,
+}
+impl PointerWidth {
+	/// The size of a pointer in bytes
+	pub fn bytes(self) -> usize {
+		match self {
+			Self::Bits32 => 4,
+			Self::Bits64 => 8,
+		}
+	}
+
+	pub(crate) fn from_is_64(is_64: bool) -> Self {
+		if is_64 {
+			Self::Bits64
+		} else {
+			Self::Bits32
+		}
+	}
+}
+
+/// The CPU architecture of an object file containing a linkstore.
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Architecture {
+	X86,
+	X86_64,
+	Arm,
+	Aarch64,
+	PowerPc,
+	PowerPc64,
+	Wasm32,
+	Wasm64,
+
+	/// The architecture is not recognised by linkstore
+	Unknown,
+}
+impl Architecture {
+	pub(crate) fn from_elf(machine: u16) -> Self {
+		use goblin::elf::header::*;
+		match machine {
+			EM_386 => Self::X86,
+			EM_X86_64 => Self::X86_64,
+			EM_ARM => Self::Arm,
+			EM_AARCH64 => Self::Aarch64,
+			EM_PPC => Self::PowerPc,
+			EM_PPC64 => Self::PowerPc64,
+			_ => Self::Unknown,
+		}
+	}
+
+	pub(crate) fn coff_pointer_width(machine: u16) -> PointerWidth {
+		use goblin::pe::header::*;
+		PointerWidth::from_is_64(matches!(machine, COFF_MACHINE_X86_64 | COFF_MACHINE_ARM64 | COFF_MACHINE_IA64))
+	}
+
+	pub(crate) fn from_coff(machine: u16) -> Self {
+		use goblin::pe::header::*;
+		match machine {
+			COFF_MACHINE_X86 => Self::X86,
+			COFF_MACHINE_X86_64 => Self::X86_64,
+			COFF_MACHINE_ARM | COFF_MACHINE_ARMNT => Self::Arm,
+			COFF_MACHINE_ARM64 => Self::Aarch64,
+			COFF_MACHINE_POWERPC => Self::PowerPc,
+			_ => Self::Unknown,
+		}
+	}
+
+	pub(crate) fn from_mach(cputype: u32) -> Self {
+		use goblin::mach::cputype::*;
+		match cputype {
+			CPU_TYPE_X86 => Self::X86,
+			CPU_TYPE_X86_64 => Self::X86_64,
+			CPU_TYPE_ARM => Self::Arm,
+			CPU_TYPE_ARM64 | CPU_TYPE_ARM64_32 => Self::Aarch64,
+			CPU_TYPE_POWERPC => Self::PowerPc,
+			CPU_TYPE_POWERPC64 => Self::PowerPc64,
+			_ => Self::Unknown,
+		}
+	}
+}
@@ -0,0 +1,260 @@
+use super::*;
+
+/// A machine-readable record of every linkstore discovered in a binary.
+///
+/// Produced by [`Embedder::export`] and applied to a binary with [`Embedder::import`].
+///
+/// With the `serde` feature enabled, the manifest can be serialized to and deserialized from any serde format.
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Manifest {
+	/// Every key discovered in the binary, sorted by name.
+	pub entries: Vec<ManifestEntry>,
+}
+impl Manifest {
+	/// Returns the entry for the given key, if present.
+	pub fn get(&self, name: &str) -> Option<&ManifestEntry> {
+		self.entries.iter().find(|entry| entry.name == name)
+	}
+
+	/// Interprets the raw bytes of every occurrence of a key using the given type hint.
+	///
+	/// The interpreted values are stored in [`ManifestOccurrence::value`].
+	///
+	/// Fails with [`Error::MismatchedSize`] if the size of the key or of any occurrence's bytes doesn't match the size of the type, in which case nothing is interpreted.
+	pub fn interpret(&mut self, name: &str, hint: TypeHint) -> Result<&mut Self, Error> {
+		let entry = self
+			.entries
+			.iter_mut()
+			.find(|entry| entry.name == name)
+			.ok_or_else(|| Error::NotPresent(name.to_string()))?;
+
+		if entry.size != hint.size() as u64 {
+			return Err(Error::MismatchedSize(entry.size, hint.size()));
+		}
+
+		// Deserialized manifests may have been edited, so the bytes don't necessarily agree with the recorded size
+		if let Some(occurrence) = entry.occurrences.iter().find(|occurrence| occurrence.bytes.len() != hint.size()) {
+			return Err(Error::MismatchedSize(occurrence.bytes.len() as u64, hint.size()));
+		}
+
+		for occurrence in entry.occurrences.iter_mut() {
+			occurrence.value = Some(hint.interpret(&occurrence.bytes));
+		}
+
+		Ok(self)
+	}
+}
+
+/// A single key in a [`Manifest`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+	/// The name of the key
+	pub name: String,
+
+	/// The size of the value in bytes
+	pub size: u64,
+
+	/// Every occurrence of the key in the binary, in the order they were discovered.
+	///
+	/// Keys can occur more than once through multiple codegen units, archive members or fat binary slices.
+	pub occurrences: Vec<ManifestOccurrence>,
+}
+
+/// A single occurrence of a key in a [`Manifest`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestOccurrence {
+	/// The file offset of the value
+	pub offset: u64,
+
+	/// The raw little-endian bytes of the value
+	pub bytes: Vec<u8>,
+
+	/// The interpreted value, if a [`TypeHint`] was given with [`Manifest::interpret`]
+	pub value: Option<ManifestValue>,
+}
+
+/// Describes how the raw bytes of a linkstore should be interpreted in a [`Manifest`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeHint {
+	Bool,
+	U8,
+	U16,
+	U32,
+	U64,
+	U128,
+	I8,
+	I16,
+	I32,
+	I64,
+	I128,
+	F32,
+	F64,
+
+	/// A fixed-length array with the given element type and length
+	Array(Box<TypeHint>, usize),
+}
+impl TypeHint {
+	/// The size in bytes of a value of this type
+	pub fn size(&self) -> usize {
+		use core::mem::size_of;
+		match self {
+			Self::Bool => size_of::<bool>(),
+			Self::U8 => size_of::<u8>(),
+			Self::U16 => size_of::<u16>(),
+			Self::U32 => size_of::<u32>(),
+			Self::U64 => size_of::<u64>(),
+			Self::U128 => size_of::<u128>(),
+			Self::I8 => size_of::<i8>(),
+			Self::I16 => size_of::<i16>(),
+			Self::I32 => size_of::<i32>(),
+			Self::I64 => size_of::<i64>(),
+			Self::I128 => size_of::<i128>(),
+			Self::F32 => size_of::<f32>(),
+			Self::F64 => size_of::<f64>(),
+			Self::Array(elem, len) => elem.size() * len,
+		}
+	}
+
+	fn interpret(&self, bytes: &[u8]) -> ManifestValue {
+		macro_rules! decode {
+			($variant:ident, $ty:ty) => {
+				ManifestValue::$variant(<$ty as DecodeLinkstore>::from_le_bytes(bytes))
+			};
+		}
+		match self {
+			Self::Bool => decode!(Bool, bool),
+			Self::U8 => decode!(U8, u8),
+			Self::U16 => decode!(U16, u16),
+			Self::U32 => decode!(U32, u32),
+			Self::U64 => decode!(U64, u64),
+			Self::U128 => decode!(U128, u128),
+			Self::I8 => decode!(I8, i8),
+			Self::I16 => decode!(I16, i16),
+			Self::I32 => decode!(I32, i32),
+			Self::I64 => decode!(I64, i64),
+			Self::I128 => decode!(I128, i128),
+			Self::F32 => decode!(F32, f32),
+			Self::F64 => decode!(F64, f64),
+			Self::Array(elem, _) => ManifestValue::Array(bytes.chunks(elem.size().max(1)).map(|chunk| elem.interpret(chunk)).collect()),
+		}
+	}
+}
+
+/// A value interpreted from the raw bytes of a linkstore using a [`TypeHint`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManifestValue {
+	Bool(bool),
+	U8(u8),
+	U16(u16),
+	U32(u32),
+	U64(u64),
+	U128(u128),
+	I8(i8),
+	I16(i16),
+	I32(i32),
+	I64(i64),
+	I128(i128),
+	F32(f32),
+	F64(f64),
+	Array(Vec<ManifestValue>),
+}
+
+impl<'a, IO> Embedder<'a, IO>
+where
+	IO: BinaryHandle<'a>,
+{
+	/// Exports every linkstore in this binary to a [`Manifest`].
+	///
+	/// Values that have been set with [`Embedder::embed`] but not yet written are exported with their new value.
+	pub fn export(&self) -> Manifest {
+		let mut entries = self
+			.embeds
+			.iter()
+			.map(|(name, embeds)| {
+				let embeds = embeds.as_ref();
+				ManifestEntry {
+					name: name.clone(),
+					size: embeds.first().map(|embed| embed.size).unwrap_or_default(),
+					occurrences: embeds
+						.iter()
+						.map(|embed| ManifestOccurrence {
+							offset: embed.location.offset,
+							bytes: embed.bytes.as_ref().to_vec(),
+							value: None,
+						})
+						.collect(),
+				}
+			})
+			.collect::<Vec<_>>();
+
+		entries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+		Manifest { entries }
+	}
+
+	/// Registers every value in a [`Manifest`] to be embedded.
+	///
+	/// If every occurrence of a key in the manifest has the same value, that value is embedded into every occurrence of the key in this binary.
+	///
+	/// Otherwise, the manifest and the binary must contain the same number of occurrences of the key, and the values are embedded in the order they were discovered.
+	///
+	/// The whole manifest is checked against the binary before anything is registered, so nothing is embedded if any entry fails.
+	pub fn import(&mut self, manifest: &Manifest) -> Result<&mut Self, Error> {
+		let mut imports = Vec::with_capacity(manifest.entries.len());
+		for entry in manifest.entries.iter() {
+			let embeds = self
+				.embeds
+				.get(&entry.name)
+				.ok_or_else(|| Error::NotPresent(entry.name.clone()))?
+				.as_ref();
+
+			let uniform = match entry.occurrences.split_first() {
+				Some((first, rest)) => rest.iter().all(|occurrence| occurrence.bytes == first.bytes),
+				None => continue,
+			};
+
+			if !uniform && entry.occurrences.len() != embeds.len() {
+				return Err(Error::MismatchedOccurrences(entry.name.clone(), embeds.len(), entry.occurrences.len()));
+			}
+
+			for (i, embed) in embeds.iter().enumerate() {
+				let bytes = &entry.occurrences[if uniform { 0 } else { i }].bytes;
+				if embed.size != bytes.len() as u64 {
+					return Err(Error::MismatchedSize(embed.size, bytes.len()));
+				}
+			}
+
+			imports.push((entry, uniform));
+		}
+
+		for (entry, uniform) in imports {
+			let Some(embeds) = self.embeds.get_mut(&entry.name) else {
+				continue;
+			};
+			for (i, embed) in embeds.as_mut().iter_mut().enumerate() {
+				let bytes = &entry.occurrences[if uniform { 0 } else { i }].bytes;
+				embed.bytes = LinkstoreBytes::Set(Cow::Owned(bytes.clone()));
+			}
+		}
+
+		let mut hash = Fnv64::new();
+		let mut entries = manifest.entries.iter().collect::<Vec<_>>();
+		entries.sort_by(|a, b| a.name.cmp(&b.name));
+		for entry in entries {
+			hash.write_key(&entry.name, entry.occurrences.iter().map(|occurrence| occurrence.bytes.as_slice()));
+		}
+		self.manifest_hash = Some(hash.finish());
+
+		Ok(self)
+	}
+}
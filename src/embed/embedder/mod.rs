@@ -1,23 +1,44 @@
 use super::{
+	container::{self, Container},
 	decode::{DecodeLinkstore, TryDecodeLinkstore},
-	encode::{EncodeLinkstore, MAGIC},
+	encode::{encode_header, EncodeLinkstore, DEFAULT_PREFIX, MAX_NAME_LEN, POOL_NAMESPACE},
 	io::BinaryHandle,
+	provenance::{Fnv64, Provenance, PROVENANCE_NAMESPACE},
 };
 use crate::Error;
 use std::{
 	borrow::Cow,
-	collections::{hash_map::Entry, HashMap},
-	io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+	collections::{hash_map::Entry, BTreeMap, HashMap},
+	io::{BufReader, Cursor, SeekFrom},
 };
 
 mod formats;
 
+#[cfg(fuzzing)]
+pub use formats::fuzz;
+
 mod util;
 use util::MaybeScalar;
 
 mod iter;
 use iter::{EmbeddedValueIterator, TryEmbeddedValueIterator};
 
+pub(crate) mod manifest;
+
+pub(crate) mod builder;
+use builder::{DiscoveryMode, EmbedderBuilder};
+
+pub(crate) mod build_id;
+use build_id::BuildIdUpdate;
+
+pub(crate) mod signature;
+use signature::{Signature, SignatureInfo, SignaturePolicy};
+
+pub(crate) mod verify;
+
+pub(crate) mod location;
+use location::{Architecture, Format, Location, PointerWidth};
+
 /// Opens a binary file in read and write mode without truncation.
 ///
 /// The returned [`std::fs::File`] is suitable for use with [`Embedder`]
@@ -28,7 +49,7 @@ pub fn open_binary<P: AsRef<std::path::Path>>(path: P) -> Result<std::fs::File,
 
 #[derive(Debug)]
 pub(crate) struct Linkstore<'a> {
-	pub(crate) offset: u64,
+	pub(crate) location: Location,
 	pub(crate) size: u64,
 	pub(crate) bytes: LinkstoreBytes<'a>,
 }
@@ -50,6 +71,59 @@ impl AsRef<[u8]> for LinkstoreBytes<'_> {
 	}
 }
 
+/// An occurrence of the pool defined with `linkstore_pool!`, into which new keys are inserted.
+#[derive(Debug)]
+pub(crate) struct Pool {
+	/// The location of the pool's contents
+	pub(crate) location: Location,
+	pub(crate) size: u64,
+
+	/// The number of bytes at the start of the pool taken up by inserted keys
+	pub(crate) used: u64,
+
+	/// The file offsets and headers of keys inserted by this embedder, which are written when it finishes
+	pub(crate) headers: Vec<(u64, Vec<u8>)>,
+}
+
+/// A key that occurs more than once in a binary, reported by [`Embedder::duplicates`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Duplicate<'a> {
+	/// The name of the key
+	pub name: &'a str,
+
+	/// The number of times the key occurs in the binary
+	pub occurrences: usize,
+
+	/// Whether every occurrence has the same size
+	pub sizes_agree: bool,
+
+	/// Whether every occurrence has the same value
+	pub values_agree: bool,
+}
+impl Duplicate<'_> {
+	/// Whether every occurrence has the same size and value
+	#[inline]
+	pub fn agree(&self) -> bool {
+		self.sizes_agree && self.values_agree
+	}
+}
+
+/// What happened when an [`Embedder`] finished writing to a binary, returned by [`Embedder::finish_with_report`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct FinishReport {
+	/// The build identifiers which were recomputed, if enabled with [`EmbedderBuilder::update_build_id`]
+	pub build_ids: Vec<BuildIdUpdate>,
+
+	/// The code signatures which were invalidated by writing into the binary, with [`SignaturePolicy::Warn`]
+	pub invalidated_signatures: Vec<Signature>,
+
+	/// The code signatures which were removed from the binary, with [`SignaturePolicy::Strip`]
+	pub stripped_signatures: Vec<Signature>,
+}
+
 #[ouroboros::self_referencing]
 pub struct OwnedObject<'a, IO>
 where
@@ -95,7 +169,29 @@ where
 	IO: BinaryHandle<'a>,
 {
 	object: OwnedObject<'a, IO>,
+	options: EmbedderBuilder,
 	pub(crate) embeds: Linkstores<'a>,
+	pools: Vec<Pool>,
+
+	/// The default values kept by `linkstore!`, by the name of the key they belong to
+	defaults: Linkstores<'a>,
+
+	/// Every occurrence of the provenance record defined with `linkstore_provenance!`
+	provenance: Vec<Linkstore<'a>>,
+
+	/// The hash of the last manifest imported, which is recorded in the provenance record
+	manifest_hash: Option<u64>,
+
+	/// The code signatures of the binary
+	signatures: Vec<SignatureInfo>,
+}
+impl Embedder<'static, std::fs::File> {
+	/// Returns an [`EmbedderBuilder`] for configuring a new [`Embedder`].
+	///
+	/// The builder can create an [`Embedder`] for any [`BinaryHandle`](crate::BinaryHandle).
+	pub fn builder() -> EmbedderBuilder {
+		EmbedderBuilder::default()
+	}
 }
 impl<'a, IO> Embedder<'a, IO>
 where
@@ -105,21 +201,41 @@ where
 	///
 	/// The handle must implement [`BinaryHandle`](crate::BinaryHandle)!
 	pub fn new(handle: &'a mut IO) -> Result<Embedder<'a, IO>, Error> {
+		Self::with_options(handle, EmbedderBuilder::default())
+	}
+
+	fn with_options(handle: &'a mut IO, options: EmbedderBuilder) -> Result<Embedder<'a, IO>, Error> {
 		let bytes = handle.get_memory()?;
 
 		let object = OwnedObjectTryBuilder {
 			handle,
 			bytes_builder: |handle| Ok(bytes.map(Cow::Owned).or_else(|| handle.get_memory_ref().map(Cow::Borrowed)).unwrap()),
-			object_builder: |bytes| Ok::<_, Error>(goblin::Object::parse(bytes.as_ref())?),
+			object_builder: |bytes| formats::parse_object(bytes.as_ref()),
 		}
 		.try_build()?;
 
 		let mut embedder = Embedder {
 			object,
+			options,
 			embeds: Linkstores::default(),
+			pools: Vec::new(),
+			defaults: Linkstores::default(),
+			provenance: Vec::new(),
+			manifest_hash: None,
+			signatures: Vec::new(),
 		};
 
 		embedder.discover_linkstores()?;
+		embedder.discover_pools()?;
+		embedder.discover_defaults();
+		embedder.discover_provenance();
+		embedder.signatures = signature::discover(embedder.object.borrow_object());
+
+		if embedder.options.strict {
+			if let Some(duplicate) = embedder.duplicates().into_iter().find(|duplicate| !duplicate.agree()) {
+				return Err(Error::DivergentDuplicates(duplicate.name.to_string()));
+			}
+		}
 
 		Ok(embedder)
 	}
@@ -130,112 +246,252 @@ where
 
 		let mut handle = BufReader::with_capacity(256, Cursor::new(bytes.as_ref()));
 
-		formats::discover_linkstores::<IO>(bytes.as_ref(), object, &mut handle, &mut self.embeds, 0)
+		let mut discovery = formats::Discovery {
+			options: &self.options,
+			embeds: &mut self.embeds,
+		};
+
+		formats::discover_linkstores::<IO>(&mut discovery, bytes.as_ref(), object, &mut handle, 0, None)
+	}
+
+	/// Takes the pools out of the discovered linkstores, and discovers the keys previously inserted into them.
+	fn discover_pools(&mut self) -> Result<(), Error> {
+		let bytes = self.object.borrow_bytes();
+		let mut handle = BufReader::with_capacity(256, Cursor::new(bytes.as_ref()));
+
+		let names = self
+			.embeds
+			.keys()
+			.filter(|name| name.rsplit_once("::").is_some_and(|(namespace, _)| namespace == POOL_NAMESPACE))
+			.cloned()
+			.collect::<Vec<_>>();
+
+		for name in names {
+			let Some(mut pools) = self.embeds.remove(&name) else {
+				continue;
+			};
+
+			for pool in core::mem::take(pools.as_vec()) {
+				// The pool's contents are decoded as if they were a section of their own, with offsets relative to the start of the binary
+				Self::decode_section(
+					&mut self.embeds,
+					&mut handle,
+					pool.location.offset,
+					pool.size,
+					&Location {
+						offset: 0,
+						..pool.location.clone()
+					},
+				)?;
+
+				let start = pool.location.offset;
+				let end = start + pool.size;
+				let used = self
+					.embeds
+					.values()
+					.flat_map(|embeds| embeds.as_ref())
+					.filter(|embed| embed.location.offset >= start && embed.location.offset < end)
+					.map(|embed| embed.location.offset + embed.size - start)
+					.max()
+					.unwrap_or(0);
+
+				self.pools.push(Pool {
+					location: pool.location,
+					size: pool.size,
+					used,
+					headers: Vec::new(),
+				});
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Takes the default values kept by `linkstore!` out of the discovered linkstores.
+	fn discover_defaults(&mut self) {
+		let names = self
+			.embeds
+			.keys()
+			.filter(|name| name.starts_with(DEFAULT_PREFIX))
+			.cloned()
+			.collect::<Vec<_>>();
+
+		for name in names {
+			if let Some(defaults) = self.embeds.remove(&name) {
+				self.defaults.insert(name[DEFAULT_PREFIX.len()..].to_string(), defaults);
+			}
+		}
+	}
+
+	/// Takes the provenance records defined with `linkstore_provenance!` out of the discovered linkstores.
+	fn discover_provenance(&mut self) {
+		let names = self
+			.embeds
+			.keys()
+			.filter(|name| name.rsplit_once("::").is_some_and(|(namespace, _)| namespace == PROVENANCE_NAMESPACE))
+			.cloned()
+			.collect::<Vec<_>>();
+
+		for name in names {
+			if let Some(mut records) = self.embeds.remove(&name) {
+				self.provenance.append(records.as_vec());
+			}
+		}
 	}
 
+	/// Scans a section for linkstores by searching for their magic bytes.
+	///
+	/// This is the fallback for binaries without a symbol table. Bytes which look like a linkstore but fail its checksum are skipped.
 	fn decode_section(
 		embeds: &mut Linkstores,
 		handle: &mut BufReader<Cursor<&[u8]>>,
 		header_offset: u64,
 		header_size: u64,
-		fat_offset: u64,
+		location: &Location,
 	) -> Result<(), Error> {
-		use core::mem::size_of;
-
-		handle.seek(SeekFrom::Start(header_offset as _))?;
-
-		let mut header_size = header_size as usize;
+		let bytes = Self::section_bytes(handle, header_offset, header_size);
 
-		// 1 magic byte
-		// 1 nul byte
-		// 2 u64
-		// the rest is variable length
-		let minimum_header_size = 1 + 1 + (size_of::<u64>() * 2);
-
-		macro_rules! read_type {
-			($ty:ty) => {{
-				let mut buf = [0u8; size_of::<$ty>()];
-				handle.read_exact(&mut buf)?;
-				<$ty>::from_le_bytes(buf)
-			}};
+		for (offset, container) in container::scan(bytes, location.pointer_width.bytes()) {
+			Self::decode_container(embeds, header_offset, header_offset + offset as u64, &container, location);
 		}
 
-		macro_rules! move_header_cursor {
-			($amount:expr) => {
-				header_size = match header_size.checked_sub($amount) {
-					Some(header_size) => header_size,
-					None => return Err(Error::UnexpectedEof),
-				}
-			};
-		}
-
-		while header_size >= minimum_header_size {
-			match handle.read_until(MAGIC, &mut Vec::new()).map_err(|err| (err.kind(), err)) {
-				Ok(n) => {
-					header_size = header_size.saturating_sub(n);
-					if header_size < minimum_header_size {
-						break;
-					}
-				}
-				Err((std::io::ErrorKind::UnexpectedEof, _)) => break,
-				Err((_, err)) => return Err(Error::IoError(err)),
-			}
+		Ok(())
+	}
 
-			let name = {
-				let mut name = Vec::with_capacity(256);
-				move_header_cursor!(handle.read_until(0, &mut name)?);
+	/// Decodes a single linkstore located by a symbol, which begins exactly at `container_offset` and spans at most `container_size` bytes.
+	///
+	/// `section_offset` is the file offset of the section containing the linkstore, which `location` describes.
+	///
+	/// Symbol tables are untrusted, so a symbol which doesn't point at a valid linkstore is skipped rather than failing discovery.
+	/// The linkstore is still found if its section is scanned.
+	fn decode_symbol(
+		embeds: &mut Linkstores,
+		handle: &mut BufReader<Cursor<&[u8]>>,
+		section_offset: u64,
+		container_offset: u64,
+		container_size: u64,
+		location: &Location,
+	) {
+		let bytes = Self::section_bytes(handle, container_offset, container_size);
+		if let Some(container) = container::parse(bytes, location.pointer_width.bytes()) {
+			Self::decode_container(embeds, section_offset, container_offset, &container, location);
+		}
+	}
 
-				if name.is_empty() {
-					return Err(Error::NameDecodingError);
-				}
+	/// Returns `size` bytes of the binary from `offset`, clamped to the end of the binary because section headers are untrusted.
+	fn section_bytes<'b>(handle: &BufReader<Cursor<&'b [u8]>>, offset: u64, size: u64) -> &'b [u8] {
+		let bytes: &'b [u8] = handle.get_ref().get_ref();
+		let start = usize::try_from(offset).unwrap_or(usize::MAX).min(bytes.len());
+		let end = start.saturating_add(usize::try_from(size).unwrap_or(usize::MAX)).min(bytes.len());
+		&bytes[start..end]
+	}
 
-				String::from_utf8_lossy(&name[0..name.len() - 1]).into_owned()
-			};
+	/// Records a linkstore parsed from the container beginning at `container_offset`, unless it has already been recorded.
+	fn decode_container(embeds: &mut Linkstores, section_offset: u64, container_offset: u64, container: &Container, location: &Location) {
+		let offset = container_offset + container.value_offset as u64;
+
+		let embed = Linkstore {
+			location: Location {
+				offset: location.offset + offset,
+				virtual_address: location.virtual_address.map(|address| address.wrapping_add(offset - section_offset)),
+				..location.clone()
+			},
+			size: container.value.len() as u64,
+			bytes: LinkstoreBytes::Unchanged(container.value.to_vec().into()),
+		};
 
-			let size = {
-				move_header_cursor!(size_of::<u64>());
-				read_type!(u64)
-			};
+		match embeds.entry(String::from_utf8_lossy(container.name).into_owned()) {
+			// Linkstores located by their symbols are found again when their section is scanned
+			Entry::Occupied(o) if o.get().as_ref().iter().any(|other| other.location.offset == embed.location.offset) => {}
+			Entry::Occupied(mut o) => {
+				o.get_mut().as_vec().push(embed);
+			}
+			Entry::Vacant(v) => {
+				v.insert(MaybeScalar::Scalar(embed));
+			}
+		}
+	}
 
-			let padding = {
-				move_header_cursor!(size_of::<u64>());
-				read_type!(u64)
-			};
+	/// Returns the fully qualified names of every linkstore key in this binary.
+	pub fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+		self.embeds.keys().map(String::as_str)
+	}
 
-			move_header_cursor!(padding as usize);
-			move_header_cursor!(size as usize);
+	/// Returns the fully qualified names of every linkstore key in this binary, grouped by namespace.
+	///
+	/// Keys defined without a namespace are grouped under `None`. Keys in each group are sorted by name.
+	pub fn namespaces(&self) -> BTreeMap<Option<&str>, Vec<&str>> {
+		let mut namespaces = BTreeMap::<_, Vec<_>>::new();
+		for name in self.keys() {
+			namespaces
+				.entry(name.rsplit_once("::").map(|(namespace, _)| namespace))
+				.or_default()
+				.push(name);
+		}
+		for names in namespaces.values_mut() {
+			names.sort_unstable();
+		}
+		namespaces
+	}
 
-			handle.seek(SeekFrom::Current(padding as _))?;
+	/// Returns the code signatures of this binary, which writing into it invalidates.
+	///
+	/// PE images signed with Authenticode and Mach-O binaries with an `LC_CODE_SIGNATURE` load command are detected, including each slice of a fat binary.
+	/// See [`EmbedderBuilder::signature_policy`] for what happens to them when the binary is written.
+	pub fn signatures(&self) -> impl Iterator<Item = &Signature> + '_ {
+		self.signatures.iter().map(|signature| &signature.signature)
+	}
 
-			let (offset, bytes) = {
-				let offset = handle.seek(SeekFrom::Current(0))?;
+	/// Returns the [`Location`] of every occurrence of a key in this binary, in the order they were discovered.
+	pub fn locations(&self, name: &str) -> Result<impl Iterator<Item = &Location> + '_, Error> {
+		Ok(self
+			.embeds
+			.get(name)
+			.ok_or_else(|| Error::NotPresent(name.to_string()))?
+			.as_ref()
+			.iter()
+			.map(|embed| &embed.location))
+	}
 
-				let bytes = {
-					let mut bytes = vec![0u8; size as usize];
-					handle.read_exact(&mut bytes)?;
-					bytes.into()
+	/// Reports every key that occurs more than once in this binary.
+	///
+	/// Keys can occur more than once through multiple codegen units, archive members or fat binary slices. Duplicates are sorted by name.
+	pub fn duplicates(&self) -> Vec<Duplicate<'_>> {
+		let mut duplicates = self
+			.embeds
+			.iter()
+			.filter_map(|(name, embeds)| {
+				let (first, rest) = match embeds.as_ref().split_first() {
+					Some((first, rest)) if !rest.is_empty() => (first, rest),
+					_ => return None,
 				};
+				Some(Duplicate {
+					name,
+					occurrences: rest.len() + 1,
+					sizes_agree: rest.iter().all(|embed| embed.size == first.size),
+					values_agree: rest.iter().all(|embed| embed.bytes.as_ref() == first.bytes.as_ref()),
+				})
+			})
+			.collect::<Vec<_>>();
+
+		duplicates.sort_unstable_by(|a, b| a.name.cmp(b.name));
+
+		duplicates
+	}
 
-				(offset, bytes)
-			};
-
-			let embed = Linkstore {
-				offset: offset + fat_offset,
-				size,
-				bytes: LinkstoreBytes::Unchanged(bytes),
-			};
+	fn get_sized<T: EncodeLinkstore>(&self, name: &str) -> Result<&[Linkstore<'a>], Error> {
+		let embeds = self.embeds.get(name).ok_or_else(|| Error::NotPresent(name.to_string()))?.as_ref();
+		for embed in embeds {
+			if T::host_layout() && !embed.location.is_host() {
+				return Err(Error::ForeignLayout(name.to_string()));
+			}
 
-			match embeds.entry(name) {
-				Entry::Occupied(mut o) => {
-					o.get_mut().as_vec().push(embed);
-				}
-				Entry::Vacant(v) => {
-					v.insert(MaybeScalar::Scalar(embed));
-				}
+			let size = T::encoded_size(embed.location.pointer_width.bytes());
+			if embed.size != size as u64 {
+				return Err(Error::MismatchedSize(embed.size, size));
 			}
 		}
-
-		Ok(())
+		Ok(embeds)
 	}
 
 	/// Attempt to fallibly decode & read a value from a linkstore in this binary.
@@ -244,11 +500,7 @@ where
 	///
 	/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
 	pub unsafe fn try_read<T: EncodeLinkstore + TryDecodeLinkstore>(&mut self, name: &str) -> Result<TryEmbeddedValueIterator<'_, T>, Error> {
-		let embeds = self.embeds.get(name).ok_or_else(|| Error::NotPresent(name.to_string()))?.as_ref();
-		if !embeds.is_empty() && embeds[0].size != core::mem::size_of::<T>() as u64 {
-			return Err(Error::MismatchedSize(embeds[0].size, core::mem::size_of::<T>()));
-		}
-		Ok(TryEmbeddedValueIterator::new(embeds))
+		Ok(TryEmbeddedValueIterator::new(self.get_sized::<T>(name)?))
 	}
 
 	/// Decode & read a value from a linkstore in this binary.
@@ -257,58 +509,390 @@ where
 	///
 	/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
 	pub unsafe fn read<T: EncodeLinkstore + DecodeLinkstore>(&mut self, name: &str) -> Result<EmbeddedValueIterator<'_, T>, Error> {
+		Ok(EmbeddedValueIterator::new(self.get_sized::<T>(name)?))
+	}
+
+	/// Decode & read exactly one value from a linkstore in this binary.
+	///
+	/// If the key occurs more than once, every occurrence must contain the same value, otherwise [`Error::DivergentDuplicates`] is returned.
+	///
+	/// ## Safety
+	///
+	/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
+	pub unsafe fn read_unique<T: EncodeLinkstore + DecodeLinkstore>(&mut self, name: &str) -> Result<T, Error> {
+		match self.get_sized::<T>(name)?.split_first() {
+			Some((first, rest)) if rest.iter().all(|embed| embed.bytes.as_ref() == first.bytes.as_ref()) => Ok(
+				DecodeLinkstore::from_target_le_bytes(first.bytes.as_ref(), first.location.pointer_width.bytes()),
+			),
+			Some(_) => Err(Error::DivergentDuplicates(name.to_string())),
+			None => Err(Error::NotPresent(name.to_string())),
+		}
+	}
+
+	/// Returns the default value of each occurrence of a key, which is the occurrence's value as it was compiled.
+	///
+	/// Each occurrence's default is the one kept in the same object file, as fat binaries and static libraries may contain different defaults for each architecture or archive member.
+	fn defaults(&self, name: &str) -> Result<Vec<&Linkstore<'a>>, Error> {
 		let embeds = self.embeds.get(name).ok_or_else(|| Error::NotPresent(name.to_string()))?.as_ref();
-		if !embeds.is_empty() && embeds[0].size != core::mem::size_of::<T>() as u64 {
-			return Err(Error::MismatchedSize(embeds[0].size, core::mem::size_of::<T>()));
+		let defaults = self.defaults.get(name).ok_or_else(|| Error::NoDefault(name.to_string()))?.as_ref();
+
+		embeds
+			.iter()
+			.map(|embed| {
+				defaults
+					.iter()
+					.find(|default| {
+						default.location.format == embed.location.format
+							&& default.location.arch == embed.location.arch
+							&& default.location.member == embed.location.member
+							&& default.size == embed.size
+					})
+					.ok_or_else(|| Error::NoDefault(name.to_string()))
+			})
+			.collect()
+	}
+
+	/// Decode & read the value a key was compiled with, which is kept by `linkstore!` with `keep_defaults = true;`.
+	///
+	/// If the key occurs more than once, every occurrence must have the same default value, otherwise [`Error::DivergentDuplicates`] is returned.
+	///
+	/// ## Safety
+	///
+	/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
+	pub unsafe fn default_value<T: EncodeLinkstore + DecodeLinkstore>(&self, name: &str) -> Result<T, Error> {
+		self.get_sized::<T>(name)?;
+		match self.defaults(name)?.split_first() {
+			Some((first, rest)) if rest.iter().all(|default| default.bytes.as_ref() == first.bytes.as_ref()) => Ok(
+				DecodeLinkstore::from_target_le_bytes(first.bytes.as_ref(), first.location.pointer_width.bytes()),
+			),
+			Some(_) => Err(Error::DivergentDuplicates(name.to_string())),
+			None => Err(Error::NotPresent(name.to_string())),
 		}
-		Ok(EmbeddedValueIterator::new(embeds))
+	}
+
+	/// Returns whether any occurrence of a key holds a value other than the one it was compiled with, including values registered by this embedder.
+	///
+	/// The key must be defined by `linkstore!` with `keep_defaults = true;`, otherwise [`Error::NoDefault`] is returned.
+	pub fn is_modified(&self, name: &str) -> Result<bool, Error> {
+		let defaults = self.defaults(name)?;
+		let embeds = self.embeds.get(name).ok_or_else(|| Error::NotPresent(name.to_string()))?.as_ref();
+
+		Ok(embeds
+			.iter()
+			.zip(defaults)
+			.any(|(embed, default)| embed.bytes.as_ref() != default.bytes.as_ref()))
+	}
+
+	/// Register every occurrence of a key to be restored to the value it was compiled with.
+	///
+	/// The key must be defined by `linkstore!` with `keep_defaults = true;`, otherwise [`Error::NoDefault`] is returned.
+	pub fn reset(&mut self, name: &str) -> Result<&mut Self, Error> {
+		let defaults = self
+			.defaults(name)?
+			.into_iter()
+			.map(|default| default.bytes.as_ref().to_vec())
+			.collect::<Vec<_>>();
+
+		let embeds = self.embeds.get_mut(name).ok_or_else(|| Error::NotPresent(name.to_string()))?;
+		for (embed, default) in embeds.as_mut().iter_mut().zip(defaults) {
+			embed.bytes = LinkstoreBytes::Set(Cow::Owned(default));
+		}
+
+		Ok(self)
+	}
+
+	/// Reads the binary's provenance record, which is defined with `linkstore_provenance!` and filled in by [`Embedder::finish`].
+	///
+	/// If the binary hasn't been stamped, every field of the record is empty. If it contains more than one record, the first is returned.
+	pub fn provenance(&self) -> Result<Provenance, Error> {
+		let record = self.provenance.first().ok_or(Error::NoProvenance)?;
+		if record.size != core::mem::size_of::<Provenance>() as u64 {
+			return Err(Error::MismatchedSize(record.size, core::mem::size_of::<Provenance>()));
+		}
+		Ok(DecodeLinkstore::from_le_bytes(record.bytes.as_ref()))
+	}
+
+	/// Returns whether any values have been registered to be written, including keys inserted into a pool.
+	fn has_pending_writes(&self) -> bool {
+		self.pools.iter().any(|pool| !pool.headers.is_empty())
+			|| self
+				.embeds
+				.values()
+				.any(|embeds| embeds.as_ref().iter().any(|embed| matches!(embed.bytes, LinkstoreBytes::Set(_))))
+	}
+
+	/// Fills in every occurrence of the provenance record, if the binary has one, with the values about to be written.
+	///
+	/// Binaries which are only read aren't stamped, so that they're left untouched.
+	fn stamp_provenance(&mut self) -> Result<(), Error> {
+		if self.provenance.is_empty() || !self.has_pending_writes() {
+			return Ok(());
+		}
+
+		// Reproducible builds set `SOURCE_DATE_EPOCH` so that stamping the same binary twice produces the same output
+		let timestamp = match std::env::var("SOURCE_DATE_EPOCH") {
+			Ok(epoch) => epoch.trim().parse::<u64>().ok(),
+			Err(_) => None,
+		}
+		.unwrap_or_else(|| {
+			std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.map(|duration| duration.as_secs())
+				.unwrap_or_default()
+		});
+
+		let mut hash = Fnv64::new();
+		let mut names = self.embeds.keys().collect::<Vec<_>>();
+		names.sort_unstable();
+		for name in names {
+			hash.write_key(name, self.embeds[name].as_ref().iter().map(|embed| embed.bytes.as_ref()));
+		}
+
+		let record = Provenance::new(
+			timestamp,
+			self.options.tool.as_deref().unwrap_or_default(),
+			hash.finish(),
+			self.manifest_hash,
+		);
+		for embed in self.provenance.iter_mut() {
+			if embed.size != core::mem::size_of::<Provenance>() as u64 {
+				return Err(Error::MismatchedSize(embed.size, core::mem::size_of::<Provenance>()));
+			}
+			embed.bytes = LinkstoreBytes::Set(record.as_le_bytes().into_owned().into());
+		}
+
+		Ok(())
 	}
 
 	/// Register a linkstore to be embedded.
 	///
-	/// ## macOS Binaries
+	/// The value is embedded into every occurrence of the key. Use [`Embedder::embed_where`] to embed into specific occurrences.
+	///
+	/// ## Signed Binaries
 	///
-	/// Because macOS binaries (namely Mach-O) are signed, patching them invalidates the signature; the signature is formed partially from the contents of the binary itself.
+	/// Because signatures are formed partially from the contents of the binary itself, patching a signed binary (such as a macOS binary or an Authenticode-signed PE image) invalidates the signature.
 	///
-	/// It's out of the scope of `linkstore` to remedy this, so please remember to resign your binaries after embedding linkstores. Most macOS machines will refuse to run binaries with missing or invalid signatures.
+	/// It's out of the scope of `linkstore` to sign binaries, so please remember to resign your binaries after embedding linkstores. Most macOS machines will refuse to run binaries with missing or invalid signatures.
+	/// [`EmbedderBuilder::signature_policy`] chooses whether signed binaries are refused, written with a warning, or have their signatures stripped so they can be signed again cleanly.
 	pub fn embed<T: EncodeLinkstore>(&mut self, name: &'a str, value: &'a T) -> Result<&mut Self, Error> {
-		let embeds = self.embeds.get_mut(name).ok_or_else(|| Error::NotPresent(name.to_string()))?;
+		self.embed_where(name, value, |_| true)
+	}
+
+	/// Register a linkstore to be embedded into the occurrences of the key for which `filter` returns `true`.
+	///
+	/// Keys can occur more than once through multiple codegen units, archive members or fat binary slices. The [`Location`] of each occurrence can be used to embed a different value per architecture or archive member.
+	///
+	/// Every matching occurrence is checked before any of them are registered, so nothing is embedded if the value can't be embedded into one of them.
+	///
+	/// If `filter` doesn't return `true` for any occurrence, nothing is embedded and `Ok` is returned,
+	/// so that the same call can stamp both fat binaries and thin binaries which lack some of the filtered architectures.
+	///
+	/// ## Example
+	///
+	/// ```no_run
+	/// use linkstore::Architecture;
+	///
+	/// let mut binary: std::fs::File = linkstore::open_binary("universal_binary").unwrap();
+	/// let mut embedder = linkstore::Embedder::new(&mut binary).unwrap();
+	///
+	/// embedder.embed_where("BACKEND_PORT", &443_u16, |location| location.arch == Architecture::Aarch64).unwrap();
+	/// embedder.embed_where("BACKEND_PORT", &8443_u16, |location| location.arch == Architecture::X86_64).unwrap();
+	///
+	/// embedder.finish().unwrap();
+	/// ```
+	pub fn embed_where<T: EncodeLinkstore, F: FnMut(&Location) -> bool>(
+		&mut self,
+		name: &'a str,
+		value: &'a T,
+		mut filter: F,
+	) -> Result<&mut Self, Error> {
+		let embeds = self.embeds.get_mut(name).ok_or_else(|| Error::NotPresent(name.to_string()))?.as_mut();
+
+		let mut values = Vec::new();
+		for (i, embed) in embeds.iter().enumerate() {
+			if !filter(&embed.location) {
+				continue;
+			}
+			if T::host_layout() && !embed.location.is_host() {
+				return Err(Error::ForeignLayout(name.to_string()));
+			}
 
-		for embed in embeds.as_mut() {
-			if embed.size != core::mem::size_of::<T>() as u64 {
-				return Err(Error::MismatchedSize(embed.size, core::mem::size_of::<T>()));
+			// `usize` and `isize` are encoded at the pointer width of each occurrence
+			let pointer_width = embed.location.pointer_width.bytes();
+			let size = T::encoded_size(pointer_width);
+			if embed.size != size as u64 {
+				return Err(Error::MismatchedSize(embed.size, size));
 			}
 
-			let bytes = value.as_le_bytes();
-			if bytes.len() != core::mem::size_of::<T>() {
-				return Err(Error::MismatchedSize(bytes.len() as u64, core::mem::size_of::<T>()));
+			let bytes = value
+				.as_target_le_bytes(pointer_width)
+				.ok_or_else(|| Error::PointerWidthOverflow(name.to_string()))?;
+			if bytes.len() != size {
+				return Err(Error::MismatchedSize(bytes.len() as u64, size));
 			}
 
-			embed.bytes = LinkstoreBytes::Set(bytes);
+			values.push((i, bytes));
+		}
+
+		for (i, bytes) in values {
+			embeds[i].bytes = LinkstoreBytes::Set(bytes);
 		}
 
 		Ok(self)
 	}
 
+	/// Register a new linkstore to be inserted into the binary's pool.
+	///
+	/// The binary must define a pool with `linkstore_pool!`, which reserves space for keys that weren't compiled into it.
+	/// The key is allocated after any keys already in the pool, and is inserted into every occurrence of the pool, such as each slice of a fat binary.
+	/// It can be read at runtime with [`linkstore::pool::get`](crate::pool::get).
+	///
+	/// Once inserted, the key behaves like any other, so it can be read or embedded into by this or any future [`Embedder`].
+	///
+	/// Fails with [`Error::InvalidName`] if the name contains a nul byte, is longer than 1024 bytes or is in a namespace reserved by linkstore.
+	///
+	/// ## Example
+	///
+	/// ```no_run
+	/// let mut binary: std::fs::File = linkstore::open_binary("my_binary").unwrap();
+	/// let mut embedder = linkstore::Embedder::new(&mut binary).unwrap();
+	///
+	/// let build = 1337_u32.to_le_bytes();
+	/// embedder.insert("build.number", &build).unwrap();
+	///
+	/// embedder.finish().unwrap();
+	/// ```
+	pub fn insert(&mut self, name: &str, bytes: &'a [u8]) -> Result<&mut Self, Error> {
+		// Reserved names are recognised as they are when the binary is discovered, so that names which merely share a prefix with them are allowed
+		let reserved = name
+			.rsplit_once("::")
+			.is_some_and(|(namespace, _)| namespace == POOL_NAMESPACE || namespace == PROVENANCE_NAMESPACE)
+			|| name.starts_with(DEFAULT_PREFIX);
+		if name.contains('\0') || name.len() > MAX_NAME_LEN || reserved {
+			return Err(Error::InvalidName(name.to_string()));
+		}
+		if self.embeds.contains_key(name) {
+			return Err(Error::AlreadyPresent(name.to_string()));
+		}
+		if self.pools.is_empty() {
+			return Err(Error::NoPool);
+		}
+
+		let headers = self
+			.pools
+			.iter()
+			.map(|pool| encode_header(name, bytes.len() as u64, 0, pool.location.pointer_width.bytes()))
+			.collect::<Vec<_>>();
+
+		// Every occurrence must have room, so that nothing is inserted if any of them are full
+		for (pool, header) in self.pools.iter().zip(headers.iter()) {
+			let len = (header.len() + bytes.len()) as u64;
+			if pool.used + len > pool.size {
+				return Err(Error::PoolFull(name.to_string(), len));
+			}
+		}
+
+		let mut embeds = Vec::with_capacity(self.pools.len());
+		for (pool, header) in self.pools.iter_mut().zip(headers) {
+			let offset = pool.used + header.len() as u64;
+			embeds.push(Linkstore {
+				location: Location {
+					offset: pool.location.offset + offset,
+					virtual_address: pool.location.virtual_address.map(|address| address.wrapping_add(offset)),
+					..pool.location.clone()
+				},
+				size: bytes.len() as u64,
+				bytes: LinkstoreBytes::Set(Cow::Borrowed(bytes)),
+			});
+
+			pool.headers.push((pool.location.offset + pool.used, header));
+			pool.used = offset + bytes.len() as u64;
+		}
+
+		self.embeds.insert(name.to_string(), MaybeScalar::from(embeds));
+
+		Ok(self)
+	}
+
 	/// Consume the Embedder and write the linkstores to the file or memory buffer.
+	///
+	/// If any values are written and the binary defines a provenance record with `linkstore_provenance!`, it is filled in with the current time, this version of linkstore,
+	/// the tool identifier given to [`EmbedderBuilder::tool`] and a hash of the values of every key. See [`Provenance`].
+	///
+	/// If the binary is code signed, its signatures are handled according to [`EmbedderBuilder::signature_policy`].
+	///
+	/// If enabled with [`EmbedderBuilder::update_build_id`] and anything is written, the binary's build identifiers are then recomputed.
+	///
+	/// Use [`Embedder::finish_with_report`] to find out which build identifiers and code signatures were changed.
 	pub fn finish(self) -> Result<(), Error> {
-		let handle = self.object.into_heads().handle;
+		self.finish_with_report().map(drop)
+	}
 
-		for (_, embeds) in self.embeds {
-			let mut write_embed = |embed: Linkstore| -> Result<(), Error> {
-				if let LinkstoreBytes::Set(bytes) = embed.bytes {
-					handle.seek(SeekFrom::Start(embed.offset))?;
-					handle.write_all(bytes.as_ref())?;
-				}
-				Ok(())
-			};
+	/// Consume the Embedder and write the linkstores like [`Embedder::finish`], returning a [`FinishReport`] of the build identifiers and code signatures it changed.
+	pub fn finish_with_report(mut self) -> Result<FinishReport, Error> {
+		self.stamp_provenance()?;
 
+		let mut writes = Vec::new();
+		for (offset, header) in self.pools.iter_mut().flat_map(|pool| pool.headers.drain(..)) {
+			writes.push((offset, Cow::Owned(header)));
+		}
+		let provenance = MaybeScalar::from(core::mem::take(&mut self.provenance));
+		for embeds in core::mem::take(&mut self.embeds).into_values().chain(core::iter::once(provenance)) {
+			let set = |embed: Linkstore<'a>| match embed.bytes {
+				LinkstoreBytes::Set(bytes) => Some((embed.location.offset, bytes)),
+				LinkstoreBytes::Unchanged(_) => None,
+			};
 			match embeds {
-				MaybeScalar::Scalar(scalar) => write_embed(scalar)?,
-				MaybeScalar::Vec(vec) => vec.into_iter().try_for_each(write_embed)?,
+				MaybeScalar::Scalar(scalar) => writes.extend(set(scalar)),
+				MaybeScalar::Vec(vec) => writes.extend(vec.into_iter().filter_map(set)),
 			}
 		}
 
-		Ok(())
+		let mut report = FinishReport::default();
+		if !writes.is_empty() && !self.signatures.is_empty() {
+			match self.options.signature_policy {
+				SignaturePolicy::Refuse => return Err(Error::Signed),
+				SignaturePolicy::Warn => {
+					report.invalidated_signatures = self.signatures.iter().map(|signature| signature.signature.clone()).collect()
+				}
+				SignaturePolicy::Strip => {
+					for signature in self.signatures.iter() {
+						let strip = signature::strip(self.object.borrow_bytes(), signature).ok_or(Error::Signed)?;
+						writes.extend(strip.into_iter().map(|(offset, bytes)| (offset, Cow::Owned(bytes))));
+						report.stripped_signatures.push(signature.signature.clone());
+					}
+				}
+			}
+		}
+
+		// Binaries which are only read are left untouched, so their identifiers still match their debug files
+		if self.options.update_build_id && !writes.is_empty() {
+			let bytes = self.object.borrow_bytes();
+			let ids = build_id::find(bytes, self.object.borrow_object());
+			if !ids.is_empty() {
+				// The identifiers are hashes of the stamped binary, so the writes are applied to a copy of it first
+				let mut contents = bytes.to_vec();
+				for (offset, bytes) in writes.iter() {
+					let start = usize::try_from(*offset).map_err(|_| Error::UnexpectedEof)?;
+					contents
+						.get_mut(start..start.saturating_add(bytes.len()))
+						.ok_or(Error::UnexpectedEof)?
+						.copy_from_slice(bytes);
+				}
+
+				report.build_ids = build_id::update(&mut contents, &ids);
+				for update in report.build_ids.iter() {
+					writes.push((update.offset, Cow::Owned(update.new.clone())));
+				}
+			}
+		}
+
+		let handle = self.object.into_heads().handle;
+		for (offset, bytes) in writes {
+			handle.seek(SeekFrom::Start(offset))?;
+			handle.write_all(bytes.as_ref())?;
+		}
+
+		Ok(report)
 	}
 }
@@ -0,0 +1,243 @@
+use super::*;
+use core::ops::Range;
+
+/// What [`Embedder::finish`] does when it writes into a binary with a code signature, which writing invalidates.
+///
+/// Set with [`EmbedderBuilder::signature_policy`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignaturePolicy {
+	/// Fail with [`Error::Signed`] without writing anything
+	Refuse,
+
+	/// Write anyway, reporting the invalidated signatures in [`FinishReport::invalidated_signatures`]
+	#[default]
+	Warn,
+
+	/// Remove the signatures, so that the binary can be signed again cleanly, reporting them in [`FinishReport::stripped_signatures`]
+	///
+	/// The certificate table's data directory entry is cleared in PE images, and the `LC_CODE_SIGNATURE` load command is removed from each Mach-O slice.
+	/// The signature data itself is left in place, but is no longer referenced by the binary.
+	Strip,
+}
+
+/// The kind of a [`Signature`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SignatureKind {
+	/// An Authenticode certificate table in a PE image
+	Authenticode,
+
+	/// An `LC_CODE_SIGNATURE` load command in a Mach-O binary or slice
+	MachOCodeSignature,
+}
+
+/// A code signature discovered in a binary, reported by [`Embedder::signatures`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+	/// The kind of signature
+	pub kind: SignatureKind,
+
+	/// The CPU architecture of the object file containing the signature, which distinguishes the slices of a fat binary
+	pub arch: Architecture,
+
+	/// The file offset of the signature data
+	pub offset: u64,
+
+	/// The size of the signature data in bytes
+	pub size: u64,
+}
+
+/// How a signature is removed from the binary.
+#[derive(Debug)]
+pub(super) enum Strip {
+	/// Zero the certificate table's data directory entry, at this file offset
+	Authenticode { entry: u64 },
+
+	/// Remove the `LC_CODE_SIGNATURE` load command from the Mach-O binary starting at `start`
+	MachO {
+		start: usize,
+		is_64: bool,
+		little_endian: bool,
+
+		/// The file offsets of the load commands
+		commands: Range<usize>,
+
+		/// The file offsets of the `LC_CODE_SIGNATURE` load command
+		command: Range<usize>,
+
+		/// The file offset of the `__LINKEDIT` segment's load command, if the signature is at the end of it
+		linkedit: Option<usize>,
+	},
+}
+
+/// A code signature, and how to remove it.
+#[derive(Debug)]
+pub(super) struct SignatureInfo {
+	pub(super) signature: Signature,
+	strip: Option<Strip>,
+}
+
+/// Discovers the code signatures of an image or executable, and of each slice of a fat Mach-O binary.
+pub(super) fn discover(object: &goblin::Object) -> Vec<SignatureInfo> {
+	match object {
+		goblin::Object::PE(pe) => discover_pe(pe).into_iter().collect(),
+		goblin::Object::Mach(goblin::mach::Mach::Binary(macho)) => discover_macho(macho, 0).into_iter().collect(),
+		goblin::Object::Mach(goblin::mach::Mach::Fat(fat)) => {
+			let mut signatures = Vec::new();
+			for (i, arch) in fat.iter_arches().enumerate() {
+				let Ok(arch) = arch else {
+					break;
+				};
+				if let Ok(goblin::mach::SingleArch::MachO(macho)) = fat.get(i) {
+					signatures.extend(discover_macho(&macho, arch.offset as usize));
+				}
+			}
+			signatures
+		}
+		_ => Vec::new(),
+	}
+}
+
+fn discover_pe(pe: &goblin::pe::PE) -> Option<SignatureInfo> {
+	// The offset of the data directories within the optional header
+	const DATA_DIRECTORIES_32: u64 = 96;
+	const DATA_DIRECTORIES_64: u64 = 112;
+	const CERTIFICATE_TABLE: u64 = 4;
+
+	let optional_header = pe.header.optional_header.as_ref()?;
+	let table = optional_header.data_directories.get_certificate_table().as_ref()?;
+	if table.size == 0 {
+		return None;
+	}
+
+	let data_directories = if pe.is_64 { DATA_DIRECTORIES_64 } else { DATA_DIRECTORIES_32 };
+	let optional_header_offset = pe.header.dos_header.pe_pointer as u64 + 4 + goblin::pe::header::SIZEOF_COFF_HEADER as u64;
+
+	Some(SignatureInfo {
+		signature: Signature {
+			kind: SignatureKind::Authenticode,
+			arch: Architecture::from_coff(pe.header.coff_header.machine),
+			// Unlike the other data directories, the certificate table's address is a file offset
+			offset: table.virtual_address as u64,
+			size: table.size as u64,
+		},
+		strip: Some(Strip::Authenticode {
+			entry: optional_header_offset + data_directories + CERTIFICATE_TABLE * 8,
+		}),
+	})
+}
+
+fn discover_macho(macho: &goblin::mach::MachO, start: usize) -> Option<SignatureInfo> {
+	use goblin::mach::{
+		header::{SIZEOF_HEADER_32, SIZEOF_HEADER_64},
+		load_command::CommandVariant,
+	};
+
+	let (index, command, signature) = macho.load_commands.iter().enumerate().find_map(|(i, command)| match command.command {
+		CommandVariant::CodeSignature(ref signature) => Some((i, command, signature)),
+		_ => None,
+	})?;
+
+	let header_size = if macho.is_64 { SIZEOF_HEADER_64 } else { SIZEOF_HEADER_32 };
+	let commands = start + header_size..start + header_size + macho.header.sizeofcmds as usize;
+	let command_range = start + command.offset..start + command.offset + signature.cmdsize as usize;
+
+	let signature_end = signature.dataoff as u64 + signature.datasize as u64;
+	let linkedit = macho.load_commands.iter().find_map(|command| {
+		let (segname, fileoff, filesize) = match command.command {
+			CommandVariant::Segment32(ref segment) => (segment.segname, segment.fileoff as u64, segment.filesize as u64),
+			CommandVariant::Segment64(ref segment) => (segment.segname, segment.fileoff, segment.filesize),
+			_ => return None,
+		};
+		(segname.starts_with(b"__LINKEDIT\0") && fileoff + filesize == signature_end).then_some(start + command.offset)
+	});
+
+	// Signing tools always place the signature last, so it can be removed without moving any other load command
+	let strip = (index == macho.load_commands.len() - 1 && command_range.end <= commands.end).then_some(Strip::MachO {
+		start,
+		is_64: macho.is_64,
+		little_endian: macho.little_endian,
+		commands,
+		command: command_range,
+		linkedit,
+	});
+
+	Some(SignatureInfo {
+		signature: Signature {
+			kind: SignatureKind::MachOCodeSignature,
+			arch: Architecture::from_mach(macho.header.cputype),
+			offset: (start as u64) + signature.dataoff as u64,
+			size: signature.datasize as u64,
+		},
+		strip,
+	})
+}
+
+/// Returns the writes which remove a signature from the binary, or `None` if it can't be removed.
+pub(super) fn strip(bytes: &[u8], signature: &SignatureInfo) -> Option<Vec<(u64, Vec<u8>)>> {
+	// Offsets of the fields being updated within the Mach-O header and segment load commands
+	const NCMDS: usize = 16;
+	const SIZEOFCMDS: usize = 20;
+	const FILEOFF_32: usize = 32;
+	const FILESIZE_32: usize = 36;
+	const FILEOFF_64: usize = 40;
+	const FILESIZE_64: usize = 48;
+
+	match *signature.strip.as_ref()? {
+		Strip::Authenticode { entry } => Some(vec![(entry, vec![0; 8])]),
+
+		Strip::MachO {
+			start,
+			is_64,
+			little_endian,
+			ref commands,
+			ref command,
+			linkedit,
+		} => {
+			let u32_bytes = |value: u32| if little_endian { value.to_le_bytes() } else { value.to_be_bytes() };
+			let read_u32 = |offset: usize| -> Option<u32> {
+				let field = bytes.get(offset..offset + 4)?.try_into().ok()?;
+				Some(if little_endian {
+					u32::from_le_bytes(field)
+				} else {
+					u32::from_be_bytes(field)
+				})
+			};
+
+			let cmdsize = (command.end - command.start) as u32;
+			let mut writes = vec![
+				(command.start as u64, vec![0; cmdsize as usize]),
+				(start as u64 + NCMDS as u64, u32_bytes(read_u32(start + NCMDS)?.checked_sub(1)?).to_vec()),
+				(
+					start as u64 + SIZEOFCMDS as u64,
+					u32_bytes(read_u32(start + SIZEOFCMDS)?.checked_sub(cmdsize)?).to_vec(),
+				),
+			];
+
+			// The signature is no longer part of the __LINKEDIT segment, as `codesign --remove-signature` would leave it
+			if let Some(linkedit) = linkedit.filter(|linkedit| commands.contains(linkedit)) {
+				let dataoff = signature.signature.offset - start as u64;
+				if is_64 {
+					let field: [u8; 8] = bytes.get(linkedit + FILEOFF_64..linkedit + FILEOFF_64 + 8)?.try_into().ok()?;
+					let fileoff = if little_endian {
+						u64::from_le_bytes(field)
+					} else {
+						u64::from_be_bytes(field)
+					};
+					let filesize = dataoff.checked_sub(fileoff)?;
+					let filesize = if little_endian { filesize.to_le_bytes() } else { filesize.to_be_bytes() };
+					writes.push(((linkedit + FILESIZE_64) as u64, filesize.to_vec()));
+				} else {
+					let fileoff = read_u32(linkedit + FILEOFF_32)? as u64;
+					let filesize = u32::try_from(dataoff.checked_sub(fileoff)?).ok()?;
+					writes.push(((linkedit + FILESIZE_32) as u64, u32_bytes(filesize).to_vec()));
+				}
+			}
+
+			Some(writes)
+		}
+	}
+}
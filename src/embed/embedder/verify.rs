@@ -0,0 +1,171 @@
+use super::*;
+
+/// A key which a binary is expected to contain, checked by [`verify`](crate::verify) and [`Embedder::verify`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectedKey<'n> {
+	/// The fully qualified name of the key
+	pub name: &'n str,
+
+	/// The size of the key's value in bytes
+	pub size: u64,
+}
+impl<'n> ExpectedKey<'n> {
+	/// Expects a key with a value of the given size.
+	pub const fn new(name: &'n str, size: u64) -> Self {
+		Self { name, size }
+	}
+
+	/// Expects a key holding a `T`, with the size of `T` on the host.
+	///
+	/// The size of a type containing `usize` or `isize` depends on the target, so use [`ExpectedKey::new`] when verifying a binary built for a different pointer width.
+	pub const fn of<T: EncodeLinkstore>(name: &'n str) -> Self {
+		Self::new(name, core::mem::size_of::<T>() as u64)
+	}
+}
+
+/// A key whose size differs from the size it was expected to have, reported by [`Verification`].
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeMismatch {
+	/// The fully qualified name of the key
+	pub name: String,
+
+	/// The size the key was expected to have
+	pub expected: u64,
+
+	/// The size of the first occurrence of the key with a different size
+	pub actual: u64,
+}
+
+/// The result of checking the keys in a binary against the keys it was expected to contain.
+///
+/// Returned by [`verify`](crate::verify) and [`Embedder::verify`]. Every list is sorted by name.
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Verification {
+	/// Expected keys which the binary doesn't contain, such as keys stripped by LTO or `--gc-sections`
+	pub missing: Vec<String>,
+
+	/// Expected keys whose size in the binary differs from their expected size
+	pub mismatched: Vec<SizeMismatch>,
+
+	/// Keys in the binary which weren't expected
+	pub unexpected: Vec<String>,
+}
+impl Verification {
+	/// Whether the binary contains exactly the expected keys, with their expected sizes.
+	pub fn is_ok(&self) -> bool {
+		self.missing.is_empty() && self.mismatched.is_empty() && self.unexpected.is_empty()
+	}
+
+	/// Panics with a message describing every problem found, unless the binary contains exactly the expected keys.
+	///
+	/// ## Example
+	///
+	/// ```no_run
+	/// use linkstore::ExpectedKey;
+	///
+	/// // In a test, fails with a message listing every missing, mismatched and unexpected key
+	/// linkstore::verify("target/release/my_binary", &[ExpectedKey::of::<u16>("app.net.port")])
+	///     .unwrap()
+	///     .assert_ok();
+	/// ```
+	#[track_caller]
+	pub fn assert_ok(&self) {
+		if !self.is_ok() {
+			panic!("{self}");
+		}
+	}
+}
+impl core::fmt::Display for Verification {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		if self.is_ok() {
+			return write!(f, "binary contains every expected linkstore");
+		}
+
+		write!(f, "binary does not contain the expected linkstores:")?;
+		for name in self.missing.iter() {
+			write!(f, "\n  missing: {name}")?;
+		}
+		for mismatch in self.mismatched.iter() {
+			write!(
+				f,
+				"\n  size mismatch: {} is {} bytes, expected {} bytes",
+				mismatch.name, mismatch.actual, mismatch.expected
+			)?;
+		}
+		for name in self.unexpected.iter() {
+			write!(f, "\n  unexpected: {name}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Checks that a binary contains the expected linkstore keys, with their expected sizes, and no others.
+///
+/// This is intended for build scripts, xtasks and tests, to catch keys stripped by the linker before the binary is stamped.
+/// The binary is only read, and its keys are discovered as they would be by [`Embedder::new`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use linkstore::ExpectedKey;
+///
+/// let verification = linkstore::verify(
+///     "target/release/my_binary",
+///     &[ExpectedKey::of::<u16>("mycrate::app.net.port"), ExpectedKey::of::<[u8; 32]>("mycrate::app.key")],
+/// )
+/// .unwrap();
+///
+/// for name in verification.missing.iter() {
+///     println!("cargo:warning=linkstore {name} was stripped from the binary");
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+pub fn verify<P: AsRef<std::path::Path>>(path: P, expected: &[ExpectedKey]) -> Result<Verification, Error> {
+	let mut binary = std::fs::read(path)?;
+	let mut cursor = Cursor::new(&mut binary[..]);
+	let embedder = Embedder::new(&mut cursor)?;
+	Ok(embedder.verify(expected))
+}
+
+impl<'a, IO> Embedder<'a, IO>
+where
+	IO: BinaryHandle<'a>,
+{
+	/// Checks that this binary contains the expected linkstore keys, with their expected sizes, and no others.
+	///
+	/// See [`verify`](crate::verify) for checking a binary on the filesystem.
+	pub fn verify(&self, expected: &[ExpectedKey]) -> Verification {
+		let mut verification = Verification::default();
+
+		for key in expected {
+			let Some(embeds) = self.embeds.get(key.name) else {
+				verification.missing.push(key.name.to_string());
+				continue;
+			};
+			if let Some(embed) = embeds.as_ref().iter().find(|embed| embed.size != key.size) {
+				verification.mismatched.push(SizeMismatch {
+					name: key.name.to_string(),
+					expected: key.size,
+					actual: embed.size,
+				});
+			}
+		}
+
+		verification.unexpected = self
+			.keys()
+			.filter(|name| expected.iter().all(|key| key.name != *name))
+			.map(str::to_string)
+			.collect();
+
+		verification.missing.sort_unstable();
+		verification.missing.dedup();
+		verification.mismatched.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+		verification.mismatched.dedup();
+		verification.unexpected.sort_unstable();
+
+		verification
+	}
+}
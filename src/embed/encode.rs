@@ -1,7 +1,52 @@
+use core::num::{NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
 use std::borrow::Cow;
 
-/// A magic byte we use to mark the beginning of a linkstore in the link section.
-pub const MAGIC: u8 = 234;
+/// Magic bytes we use to mark the beginning of a linkstore in the link section.
+pub const MAGIC: [u8; 4] = [234, b'L', b'N', b'K'];
+
+/// The maximum length in bytes of a linkstore's fully qualified name.
+///
+/// This bounds the search for the nul byte that ends the name when scanning for linkstores.
+pub const MAX_NAME_LEN: usize = 1024;
+
+/// Namespace of the keys reserved for pools defined with `linkstore_pool!`, which must match the namespace used by the macro.
+#[cfg(any(feature = "embedder", feature = "store"))]
+pub const POOL_NAMESPACE: &str = "linkstore::pool";
+
+/// Prefix of the keys holding the default values kept by `linkstore!` with `keep_defaults = true;`, which must match the prefix used by the macro.
+#[cfg(feature = "embedder")]
+pub const DEFAULT_PREFIX: &str = "linkstore::default::";
+
+/// Computes the checksum which follows a linkstore's header, so that bytes which merely look like a header can be told apart from a real linkstore.
+///
+/// This is a 32-bit FNV-1a hash of the name, size and padding, as they are stored in the binary.
+pub const fn header_checksum(name: &[u8], size: &[u8], padding: &[u8]) -> u32 {
+	const fn fnv1a(mut hash: u32, bytes: &[u8]) -> u32 {
+		let mut i = 0;
+		while i < bytes.len() {
+			hash ^= bytes[i] as u32;
+			hash = hash.wrapping_mul(0x0100_0193);
+			i += 1;
+		}
+		hash
+	}
+	fnv1a(fnv1a(fnv1a(0x811C_9DC5, name), size), padding)
+}
+
+/// Encodes the header which precedes a linkstore's padding and value, storing the size and padding in `pointer_width` bytes.
+#[cfg(feature = "embedder")]
+pub(crate) fn encode_header(name: &str, size: u64, padding: u64, pointer_width: usize) -> Vec<u8> {
+	let size = &size.to_le_bytes()[..pointer_width];
+	let padding = &padding.to_le_bytes()[..pointer_width];
+
+	let mut header = MAGIC.to_vec();
+	header.extend_from_slice(name.as_bytes());
+	header.push(0);
+	header.extend_from_slice(size);
+	header.extend_from_slice(padding);
+	header.extend_from_slice(&header_checksum(name.as_bytes(), size, padding).to_le_bytes());
+	header
+}
 
 /// Implemented for types that can be encoded into a linkstore.
 ///
@@ -10,6 +55,49 @@ pub const MAGIC: u8 = 234;
 /// Implementing this trait is extremely unsafe. The bytes will be effectively [`core::mem::transmute`]d into the type in the compiled binary, so the bytes must be valid and normalized to little-endian if applicable.
 pub unsafe trait EncodeLinkstore {
 	fn as_le_bytes(&self) -> Cow<'_, [u8]>;
+
+	/// The size of the encoded value in a binary whose `usize` is `pointer_width` bytes.
+	///
+	/// This only differs from `size_of::<Self>()` for types containing `usize` or `isize`, whose size depends on the target.
+	#[doc(hidden)]
+	fn encoded_size(pointer_width: usize) -> usize
+	where
+		Self: Sized,
+	{
+		let _ = pointer_width;
+		core::mem::size_of::<Self>()
+	}
+
+	/// Encodes the value for a binary whose `usize` is `pointer_width` bytes.
+	///
+	/// Returns `None` if the value can't be represented on the target, such as a `usize` which is too big for a 32-bit target.
+	#[doc(hidden)]
+	fn as_target_le_bytes(&self, pointer_width: usize) -> Option<Cow<'_, [u8]>> {
+		let _ = pointer_width;
+		Some(self.as_le_bytes())
+	}
+
+	/// Whether the value is encoded in the host compiler's layout of the type, such as a tuple, whose fields may be ordered and aligned differently on other targets.
+	///
+	/// The [`Embedder`](crate::Embedder) only reads and writes these types in binaries built for the same target as itself.
+	#[doc(hidden)]
+	fn host_layout() -> bool
+	where
+		Self: Sized,
+	{
+		false
+	}
+}
+
+/// Truncates a sign- or zero-extended 64-bit integer to `pointer_width` bytes, returning `None` if it doesn't fit.
+pub(crate) fn truncate_pointer_sized(value: [u8; 8], signed: bool, pointer_width: usize) -> Option<Vec<u8>> {
+	let (bytes, extension) = value.split_at(pointer_width);
+	let fill = if signed && bytes.last().is_some_and(|byte| byte & 0x80 != 0) {
+		0xFF
+	} else {
+		0
+	};
+	extension.iter().all(|byte| *byte == fill).then(|| bytes.to_vec())
 }
 
 unsafe impl EncodeLinkstore for bool {
@@ -22,14 +110,75 @@ unsafe impl EncodeLinkstore for bool {
 	}
 }
 
+/// Elements are laid out `stride` bytes apart, with any padding between them zeroed.
 unsafe impl<T: EncodeLinkstore, const N: usize> EncodeLinkstore for [T; N] {
 	fn as_le_bytes(&self) -> Cow<'_, [u8]> {
-		let mut bytes = Vec::with_capacity(self.len() * core::mem::size_of::<T>());
-		for elem in self {
-			bytes.extend_from_slice(elem.as_le_bytes().as_ref());
-		}
-		Cow::Owned(bytes)
+		encode_array(self, core::mem::size_of::<T>(), |elem| Some(elem.as_le_bytes())).unwrap()
 	}
+
+	fn encoded_size(pointer_width: usize) -> usize {
+		T::encoded_size(pointer_width) * N
+	}
+
+	fn as_target_le_bytes(&self, pointer_width: usize) -> Option<Cow<'_, [u8]>> {
+		encode_array(self, T::encoded_size(pointer_width), |elem| elem.as_target_le_bytes(pointer_width))
+	}
+
+	fn host_layout() -> bool {
+		T::host_layout()
+	}
+}
+
+/// Encodes the elements of an array `stride` bytes apart, returning `None` if any element can't be encoded.
+fn encode_array<'t, T: EncodeLinkstore>(
+	elems: &'t [T],
+	stride: usize,
+	mut encode: impl FnMut(&'t T) -> Option<Cow<'t, [u8]>>,
+) -> Option<Cow<'t, [u8]>> {
+	let mut bytes = vec![0u8; elems.len() * stride];
+	if stride == 0 {
+		return Some(Cow::Owned(bytes));
+	}
+	for (elem, chunk) in elems.iter().zip(bytes.chunks_exact_mut(stride)) {
+		let elem = encode(elem)?;
+		chunk[..elem.len()].copy_from_slice(&elem);
+	}
+	Some(Cow::Owned(bytes))
+}
+
+/// Fields are encoded at their offsets in the host's layout of the tuple, with any padding between them zeroed, so tuples are only embedded into binaries built for the host's target.
+macro_rules! impl_tuples {
+	($(($($ty:ident $idx:tt),+))+) => {$(
+		unsafe impl<$($ty: EncodeLinkstore),+> EncodeLinkstore for ($($ty,)+) {
+			fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+				let mut bytes = vec![0u8; core::mem::size_of::<Self>()];
+				$({
+					let offset = core::mem::offset_of!(Self, $idx);
+					let field = self.$idx.as_le_bytes();
+					bytes[offset..offset + field.len()].copy_from_slice(&field);
+				})+
+				Cow::Owned(bytes)
+			}
+
+			fn host_layout() -> bool {
+				true
+			}
+		}
+	)+}
+}
+impl_tuples! {
+	(A 0)
+	(A 0, B 1)
+	(A 0, B 1, C 2)
+	(A 0, B 1, C 2, D 3)
+	(A 0, B 1, C 2, D 3, E 4)
+	(A 0, B 1, C 2, D 3, E 4, F 5)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
 }
 
 macro_rules! impl_numbers {
@@ -42,3 +191,56 @@ macro_rules! impl_numbers {
 	)+}
 }
 impl_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+macro_rules! impl_pointer_sized {
+	($($ty:ty => $wide:ty),+) => {$(
+		unsafe impl EncodeLinkstore for $ty {
+			fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+				self.to_le_bytes().to_vec().into()
+			}
+
+			fn encoded_size(pointer_width: usize) -> usize {
+				pointer_width
+			}
+
+			fn as_target_le_bytes(&self, pointer_width: usize) -> Option<Cow<'_, [u8]>> {
+				truncate_pointer_sized((*self as $wide).to_le_bytes(), <$ty>::MIN != 0, pointer_width).map(Cow::Owned)
+			}
+		}
+	)+}
+}
+impl_pointer_sized!(usize => u64, isize => i64);
+
+unsafe impl EncodeLinkstore for char {
+	fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+		(*self as u32).to_le_bytes().to_vec().into()
+	}
+}
+
+macro_rules! impl_non_zero {
+	($($ty:ty),+) => {$(
+		unsafe impl EncodeLinkstore for $ty {
+			fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+				self.get().to_le_bytes().to_vec().into()
+			}
+		}
+
+		unsafe impl EncodeLinkstore for Option<$ty> {
+			fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+				self.map_or(0, <$ty>::get).to_le_bytes().to_vec().into()
+			}
+		}
+	)+}
+}
+impl_non_zero!(
+	NonZeroU8,
+	NonZeroU16,
+	NonZeroU32,
+	NonZeroU64,
+	NonZeroU128,
+	NonZeroI8,
+	NonZeroI16,
+	NonZeroI32,
+	NonZeroI64,
+	NonZeroI128
+);
@@ -3,5 +3,10 @@ pub(crate) mod embedder;
 
 pub(crate) mod io;
 
+pub(crate) mod container;
 pub(crate) mod decode;
 pub(crate) mod encode;
+pub(crate) mod provenance;
+
+#[cfg(feature = "embedder")]
+pub(crate) mod target;
@@ -0,0 +1,187 @@
+//! The record of who stamped a binary and when, kept in the linkstore defined by `linkstore_provenance!`.
+
+use super::{
+	decode::{AnyBitPattern, DecodeLinkstore, TryDecodeLinkstore},
+	encode::EncodeLinkstore,
+};
+use std::borrow::Cow;
+
+/// Namespace of the keys reserved for records defined with `linkstore_provenance!`, which must match the namespace used by the macro.
+#[cfg(feature = "embedder")]
+pub const PROVENANCE_NAMESPACE: &str = "linkstore::provenance";
+
+/// A record of the tool which last stamped a binary, when, and with which values.
+///
+/// A binary keeps a provenance record if it defines one with [`linkstore_provenance!`](crate::linkstore_provenance), which [`Embedder::finish`](crate::Embedder::finish) fills in
+/// every time the binary is stamped. It can be read back at runtime through the linkstore's module, or with [`Embedder::provenance`](crate::Embedder::provenance).
+///
+/// Until the binary has been stamped, every field is empty and [`Provenance::is_stamped`] returns `false`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Provenance {
+	timestamp: [u8; 8],
+	values_hash: [u8; 8],
+	manifest_hash: [u8; 8],
+	version: [u8; Self::VERSION_LEN],
+	tool: [u8; Self::TOOL_LEN],
+}
+impl Provenance {
+	/// The record compiled into a binary, before it has been stamped.
+	pub const UNSTAMPED: Self = Self {
+		timestamp: [0; 8],
+		values_hash: [0; 8],
+		manifest_hash: [0; 8],
+		version: [0; Self::VERSION_LEN],
+		tool: [0; Self::TOOL_LEN],
+	};
+
+	/// The maximum length in bytes of the linkstore version.
+	pub const VERSION_LEN: usize = 32;
+
+	/// The maximum length in bytes of the tool identifier, beyond which it is truncated.
+	pub const TOOL_LEN: usize = 64;
+
+	#[cfg(feature = "embedder")]
+	pub(crate) fn new(timestamp: u64, tool: &str, values_hash: u64, manifest_hash: Option<u64>) -> Self {
+		fn fill<const N: usize>(s: &str) -> [u8; N] {
+			// Truncated at a character boundary, so that the field remains valid UTF-8
+			let mut len = s.len().min(N);
+			while !s.is_char_boundary(len) {
+				len -= 1;
+			}
+
+			let mut bytes = [0; N];
+			bytes[..len].copy_from_slice(&s.as_bytes()[..len]);
+			bytes
+		}
+
+		Self {
+			timestamp: timestamp.to_le_bytes(),
+			values_hash: values_hash.to_le_bytes(),
+			manifest_hash: manifest_hash.unwrap_or(0).to_le_bytes(),
+			version: fill(env!("CARGO_PKG_VERSION")),
+			tool: fill(tool),
+		}
+	}
+
+	/// Whether the binary has been stamped since it was compiled.
+	#[inline]
+	pub fn is_stamped(&self) -> bool {
+		!self.version().is_empty()
+	}
+
+	/// When the binary was stamped, in seconds since the Unix epoch.
+	///
+	/// If the `SOURCE_DATE_EPOCH` environment variable was set when it was stamped, this is its value rather than the current time.
+	#[inline]
+	pub fn timestamp(&self) -> u64 {
+		u64::from_le_bytes(self.timestamp)
+	}
+
+	/// The version of linkstore which stamped the binary.
+	#[inline]
+	pub fn version(&self) -> &str {
+		nul_terminated(&self.version)
+	}
+
+	/// The tool identifier given to [`EmbedderBuilder::tool`](crate::EmbedderBuilder::tool) when the binary was stamped.
+	#[inline]
+	pub fn tool(&self) -> &str {
+		nul_terminated(&self.tool)
+	}
+
+	/// A 64-bit FNV-1a hash of the name and value of every key in the binary after it was stamped, excluding the provenance record itself.
+	#[inline]
+	pub fn values_hash(&self) -> u64 {
+		u64::from_le_bytes(self.values_hash)
+	}
+
+	/// A 64-bit FNV-1a hash of the [`Manifest`](crate::Manifest) imported when the binary was stamped, if any.
+	#[inline]
+	pub fn manifest_hash(&self) -> Option<u64> {
+		Some(u64::from_le_bytes(self.manifest_hash)).filter(|hash| *hash != 0)
+	}
+}
+impl core::fmt::Debug for Provenance {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Provenance")
+			.field("timestamp", &self.timestamp())
+			.field("version", &self.version())
+			.field("tool", &self.tool())
+			.field("values_hash", &format_args!("{:016x}", self.values_hash()))
+			.field("manifest_hash", &self.manifest_hash().map(|hash| format!("{hash:016x}")))
+			.finish()
+	}
+}
+
+/// Returns the valid UTF-8 before the first nul byte.
+fn nul_terminated(bytes: &[u8]) -> &str {
+	let bytes = &bytes[..bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len())];
+	match core::str::from_utf8(bytes) {
+		Ok(s) => s,
+		Err(err) => core::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap(),
+	}
+}
+
+unsafe impl EncodeLinkstore for Provenance {
+	fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+		[&self.timestamp[..], &self.values_hash, &self.manifest_hash, &self.version, &self.tool]
+			.concat()
+			.into()
+	}
+}
+
+unsafe impl DecodeLinkstore for Provenance {
+	fn from_le_bytes(bytes: &[u8]) -> Self {
+		let (timestamp, bytes) = bytes.split_at(8);
+		let (values_hash, bytes) = bytes.split_at(8);
+		let (manifest_hash, bytes) = bytes.split_at(8);
+		let (version, tool) = bytes.split_at(Self::VERSION_LEN);
+		Self {
+			timestamp: timestamp.try_into().unwrap(),
+			values_hash: values_hash.try_into().unwrap(),
+			manifest_hash: manifest_hash.try_into().unwrap(),
+			version: version.try_into().unwrap(),
+			tool: tool.try_into().unwrap(),
+		}
+	}
+}
+unsafe impl TryDecodeLinkstore for Provenance {
+	type Error = core::convert::Infallible;
+
+	fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+		Ok(<Self as DecodeLinkstore>::from_le_bytes(bytes))
+	}
+}
+unsafe impl AnyBitPattern for Provenance {}
+
+/// A 64-bit FNV-1a hash, which is stable across hosts and versions so that it can be stored in binaries.
+#[cfg(feature = "embedder")]
+pub(crate) struct Fnv64(u64);
+#[cfg(feature = "embedder")]
+impl Fnv64 {
+	pub(crate) fn new() -> Self {
+		Self(0xCBF2_9CE4_8422_2325)
+	}
+
+	pub(crate) fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= *byte as u64;
+			self.0 = self.0.wrapping_mul(0x0000_0100_0000_01B3);
+		}
+	}
+
+	/// Hashes a key's name and the value of each of its occurrences.
+	pub(crate) fn write_key<'b>(&mut self, name: &str, values: impl Iterator<Item = &'b [u8]>) {
+		self.write(name.as_bytes());
+		self.write(&[0]);
+		for value in values {
+			self.write(&(value.len() as u64).to_le_bytes());
+			self.write(value);
+		}
+	}
+
+	pub(crate) fn finish(&self) -> u64 {
+		self.0
+	}
+}
@@ -0,0 +1,58 @@
+//! Host-side representations of the target's pointer-sized integers, for stamping binaries whose pointer width differs from the host's.
+
+use super::{
+	decode::{extend_pointer_sized, DecodeLinkstore, TryDecodeLinkstore},
+	encode::{truncate_pointer_sized, EncodeLinkstore},
+};
+use std::borrow::Cow;
+
+macro_rules! target_pointer_sized {
+	($($(#[$attr:meta])* $name:ident($wide:ty), $signed:literal;)+) => {$(
+		$(#[$attr])*
+		#[cfg_attr(docsrs, doc(cfg(feature = "embedder")))]
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+		pub struct $name(pub $wide);
+
+		unsafe impl EncodeLinkstore for $name {
+			fn as_le_bytes(&self) -> Cow<'_, [u8]> {
+				self.0.to_le_bytes().to_vec().into()
+			}
+
+			fn encoded_size(pointer_width: usize) -> usize {
+				pointer_width
+			}
+
+			fn as_target_le_bytes(&self, pointer_width: usize) -> Option<Cow<'_, [u8]>> {
+				truncate_pointer_sized(self.0.to_le_bytes(), $signed, pointer_width).map(Cow::Owned)
+			}
+		}
+
+		unsafe impl DecodeLinkstore for $name {
+			fn from_le_bytes(bytes: &[u8]) -> Self {
+				Self(<$wide>::from_le_bytes(extend_pointer_sized(bytes, $signed)))
+			}
+		}
+		unsafe impl TryDecodeLinkstore for $name {
+			type Error = core::convert::Infallible;
+
+			fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+				Ok(<Self as DecodeLinkstore>::from_le_bytes(bytes))
+			}
+		}
+	)+};
+}
+target_pointer_sized! {
+	/// A `usize` in the binary being stamped, whose width is taken from the binary rather than the host.
+	///
+	/// `usize` can also be read and written by an [`Embedder`](crate::Embedder), but a 32-bit host can't represent every `usize` of a 64-bit target.
+	///
+	/// Writing a value which doesn't fit in the target's `usize` fails with [`Error::PointerWidthOverflow`](crate::Error::PointerWidthOverflow).
+	TargetUsize(u64), false;
+
+	/// An `isize` in the binary being stamped, whose width is taken from the binary rather than the host.
+	///
+	/// `isize` can also be read and written by an [`Embedder`](crate::Embedder), but a 32-bit host can't represent every `isize` of a 64-bit target.
+	///
+	/// Writing a value which doesn't fit in the target's `isize` fails with [`Error::PointerWidthOverflow`](crate::Error::PointerWidthOverflow).
+	TargetIsize(i64), true;
+}
@@ -0,0 +1,163 @@
+//! Overrides of linkstore values from environment variables, used by `linkstore!` when the `env-override` feature is enabled.
+//!
+//! Types are dispatched with autoref specialization, so that keys of types which can't be parsed from a string still compile and are never overridden.
+
+use core::marker::PhantomData;
+use std::sync::OnceLock;
+
+/// Returns the environment variable which overrides a key, which is its fully qualified name in uppercase prefixed with `LINKSTORE_`.
+///
+/// Characters which can't be used in an environment variable name, such as the `::` separating a namespace, are replaced with underscores.
+pub fn env_var_name(name: &str) -> String {
+	let name = name
+		.chars()
+		.map(|char| if char.is_ascii_alphanumeric() { char.to_ascii_uppercase() } else { '_' });
+	"LINKSTORE_".chars().chain(name).collect()
+}
+
+/// Implemented for types that can be parsed from an environment variable.
+pub trait ParseEnvOverride: Sized {
+	fn parse_env_override(value: &str) -> Option<Self>;
+
+	/// Parses an array of this type, whose elements are separated by commas.
+	fn parse_env_override_array<const N: usize>(value: &str) -> Option<[Self; N]> {
+		parse_separated(value)
+	}
+}
+
+/// Parses an array from exactly `N` elements separated by commas.
+fn parse_separated<T: ParseEnvOverride, const N: usize>(value: &str) -> Option<[T; N]> {
+	let elements = value
+		.split(',')
+		.map(|element| T::parse_env_override(element.trim()))
+		.collect::<Option<Vec<_>>>()?;
+	elements.try_into().ok()
+}
+
+impl ParseEnvOverride for bool {
+	fn parse_env_override(value: &str) -> Option<Self> {
+		match value {
+			"true" | "1" => Some(true),
+			"false" | "0" => Some(false),
+			_ => None,
+		}
+	}
+}
+
+/// Arrays are parsed from their elements separated by commas.
+impl<T: ParseEnvOverride, const N: usize> ParseEnvOverride for [T; N] {
+	fn parse_env_override(value: &str) -> Option<Self> {
+		T::parse_env_override_array(value)
+	}
+}
+
+macro_rules! impl_integers {
+	($($ty:ty $({$($array:tt)+})?),+) => {$(
+		/// Integers are parsed from decimal, or from hex if prefixed with `0x`.
+		impl ParseEnvOverride for $ty {
+			fn parse_env_override(value: &str) -> Option<Self> {
+				match value.strip_prefix("0x") {
+					Some(hex) => <$ty>::from_str_radix(hex, 16).ok(),
+					None => value.parse().ok(),
+				}
+			}
+			$($($array)+)?
+		}
+	)+}
+}
+impl_integers!(
+	u8 {
+		/// Byte arrays are also parsed from hex, optionally prefixed with `0x`, if they don't contain any commas.
+		fn parse_env_override_array<const N: usize>(value: &str) -> Option<[u8; N]> {
+			if value.contains(',') {
+				return parse_separated(value);
+			}
+
+			let value = value.strip_prefix("0x").unwrap_or(value);
+			if value.len() != N * 2 || !value.is_ascii() {
+				return None;
+			}
+
+			let mut bytes = [0u8; N];
+			for (byte, hex) in bytes.iter_mut().zip(value.as_bytes().chunks_exact(2)) {
+				*byte = u8::from_str_radix(core::str::from_utf8(hex).ok()?, 16).ok()?;
+			}
+			Some(bytes)
+		}
+	},
+	u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+impl ParseEnvOverride for f32 {
+	fn parse_env_override(value: &str) -> Option<Self> {
+		value.parse().ok()
+	}
+}
+
+impl ParseEnvOverride for f64 {
+	fn parse_env_override(value: &str) -> Option<Self> {
+		value.parse().ok()
+	}
+}
+
+/// Selects how a key of type `T` is overridden.
+pub struct EnvOverride<T>(PhantomData<T>);
+impl<T> EnvOverride<T> {
+	#[doc(hidden)]
+	#[allow(clippy::new_without_default)]
+	pub const fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+/// Reports an environment variable which is set but can't be used to override a key, without failing the lookup.
+fn report(var: &str, reason: core::fmt::Arguments<'_>) {
+	eprintln!("linkstore: ignoring {var}, {reason}");
+}
+
+/// Overrides keys of types which can be parsed from an environment variable.
+///
+/// Values which can't be parsed are reported and ignored, so the key falls back to its embedded value.
+pub trait ParseEnvOverrideKind<T> {
+	fn env_override(&self, name: &str) -> Option<T>;
+}
+impl<T: ParseEnvOverride> ParseEnvOverrideKind<T> for &EnvOverride<T> {
+	fn env_override(&self, name: &str) -> Option<T> {
+		let var = env_var_name(name);
+		let value = std::env::var(&var).ok()?;
+		let parsed = T::parse_env_override(value.trim());
+		if parsed.is_none() {
+			report(&var, format_args!("{value:?} is not a valid {}", core::any::type_name::<T>()));
+		}
+		parsed
+	}
+}
+
+/// Never overrides keys of types which can't be parsed from an environment variable, but reports the variable if it's set.
+pub trait NoEnvOverrideKind<T> {
+	fn env_override(&self, name: &str) -> Option<T> {
+		let var = env_var_name(name);
+		if std::env::var_os(&var).is_some() {
+			report(&var, format_args!("{} can't be overridden", core::any::type_name::<T>()));
+		}
+		None
+	}
+}
+impl<T> NoEnvOverrideKind<T> for EnvOverride<T> {}
+
+/// Holds a key's override, which is read from the environment the first time the key is accessed.
+///
+/// It's only `Sync` if the key's type is, as the override is shared by every thread accessing the key.
+pub struct EnvOverrideCell<T>(OnceLock<Option<T>>);
+impl<T> EnvOverrideCell<T> {
+	#[doc(hidden)]
+	#[allow(clippy::new_without_default)]
+	pub const fn new() -> Self {
+		Self(OnceLock::new())
+	}
+
+	#[doc(hidden)]
+	pub fn get_or_init(&'static self, init: impl FnOnce() -> Option<T>) -> Option<&'static T> {
+		self.0.get_or_init(init).as_ref()
+	}
+}
@@ -2,11 +2,14 @@
 
 //! linkstore is a library that allows you to define global variables in your final compiled binary that can be modified post-compilation.
 //!
-//! linkstore currently supports ELF and PE executable formats and can be used with both statically and dynamically linked libraries.
+//! linkstore currently supports ELF, PE, Mach-O and WebAssembly executable formats and can be used with both statically and dynamically linked libraries.
 //!
 //! # Supported types
 //!
-//! Currently, linkstore can serialize and deserialize numbers (excluding `usize` and `isize`), `bool` and fixed-length arrays out of the box.
+//! Currently, linkstore can serialize and deserialize numbers, `bool`, `char`, `NonZero*` integers, `Option<NonZero*>`, fixed-length arrays and tuples of up to 12 elements out of the box. Tuples are encoded in the host's layout, including zeroed padding, so the [`Embedder`] only reads and writes them in binaries built for the same target as itself, and fails with [`Error::ForeignLayout`] otherwise. Fieldless enums can be stored by defining them with `linkstore_enum!`.
+//!
+//! `usize` and `isize` are stored at the target's pointer width, which the [`Embedder`] takes from the binary. Use [`TargetUsize`] and [`TargetIsize`] to stamp
+//! values that the host's `usize` and `isize` can't represent.
 //!
 //! For anything else, you'll need to implement your own deserialization from fixed-length byte arrays.
 //!
@@ -36,6 +39,8 @@
 //! }
 //! ```
 //!
+//! `get()` returns a reference to the value without validating it, so it's only available for types where every bit pattern is valid, such as numbers. Other types, such as `bool`, are read with the safe `try_get()`, which returns an error if the value has been patched with invalid bytes.
+//!
 //! ## Manipulating linkstore globals after compilation
 //!
 //! Once your binary has been built, you can use linkstore to modify the values.
@@ -68,13 +73,30 @@ compile_error!("Please enable at least one of the following features for linksto
 #[cfg(test)]
 mod tests;
 
+#[cfg(any(test, feature = "testing"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
 #[cfg(feature = "store")]
 mod store;
 
+#[cfg(feature = "store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "store")))]
+pub mod pool;
+
+#[cfg(feature = "store")]
+mod lookup;
+
+#[cfg(feature = "env-override")]
+mod env_override;
+
 mod embed;
 
 /// Errors that can occur when using linkstore.
+///
+/// New variants may be added in minor releases, so matches on this enum need a wildcard arm.
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
 	/// An error occured whilst parsing the executable format.
 	#[error("{0}")]
@@ -88,6 +110,50 @@ pub enum Error {
 	#[error("Linkstore contains no key with name {0}")]
 	NotPresent(String),
 
+	/// Binary already contains a linkstore with this name, so it can't be inserted
+	#[error("Linkstore already contains a key with name {0}")]
+	AlreadyPresent(String),
+
+	/// The name can't be used for a linkstore key
+	#[error("Invalid linkstore key name {0:?}")]
+	InvalidName(String),
+
+	/// The linkstore wasn't defined with `keep_defaults = true;`, so the binary doesn't contain its default value
+	#[error("Linkstore {0} does not keep its default value")]
+	NoDefault(String),
+
+	/// Binary doesn't contain a pool defined with `linkstore_pool!`
+	#[error("Binary does not contain a linkstore pool")]
+	NoPool,
+
+	/// The binary's pool doesn't have enough free space for a new key
+	#[error("Linkstore pool does not have room for key {0} ({1} bytes)")]
+	PoolFull(String, u64),
+
+	/// Binary doesn't contain a provenance record defined with `linkstore_provenance!`
+	#[error("Binary does not contain a linkstore provenance record")]
+	NoProvenance,
+
+	/// Binary has a code signature which embedding would invalidate, and the [`SignaturePolicy`] refuses to, or the signature can't be stripped
+	#[error("Binary is code signed, and embedding would invalidate its signature")]
+	Signed,
+
+	/// A manifest contains a different number of occurrences of a key than the binary
+	#[error("Binary contains {1} occurrences of linkstore {0}, but the manifest contains {2}")]
+	MismatchedOccurrences(String, usize, usize),
+
+	/// The value doesn't fit in the target's `usize` or `isize`
+	#[error("Value of linkstore {0} does not fit in the target's pointer width")]
+	PointerWidthOverflow(String),
+
+	/// The value's type is laid out by the host's compiler, such as a tuple, and the binary was built for a different target
+	#[error("Linkstore {0} has a type which can only be read or written in binaries built for the same target as this tool")]
+	ForeignLayout(String),
+
+	/// A key occurs more than once in the binary with differing sizes or values
+	#[error("Linkstore {0} occurs more than once with differing sizes or values")]
+	DivergentDuplicates(String),
+
 	/// Binary doesn't contain any linkstores
 	#[error("Binary does not contain a Linkstore section")]
 	NoLinkstore,
@@ -117,16 +183,40 @@ pub enum Error {
 pub use goblin;
 
 pub use embed::{
-	decode::{DecodeLinkstore, TryDecodeLinkstore},
+	decode::{
+		AnyBitPattern, DecodeLinkstore, TryDecodeLinkstore, TryDecodeLinkstoreArrayError, TryDecodeLinkstoreBoolError, TryDecodeLinkstoreCharError,
+		TryDecodeLinkstoreEnumError, TryDecodeLinkstoreNonZeroError, TryDecodeLinkstoreTupleError,
+	},
 	encode::EncodeLinkstore,
+	provenance::Provenance,
 };
 
 #[cfg(feature = "embedder")]
-pub use embed::embedder::{open_binary, Embedder};
+pub use embed::embedder::{
+	build_id::{BuildIdKind, BuildIdUpdate},
+	builder::{DiscoveryMode, EmbedderBuilder},
+	location::{Architecture, Format, Location, PointerWidth},
+	manifest::{Manifest, ManifestEntry, ManifestOccurrence, ManifestValue, TypeHint},
+	open_binary,
+	signature::{Signature, SignatureKind, SignaturePolicy},
+	verify::{verify, ExpectedKey, SizeMismatch, Verification},
+	Duplicate, Embedder, FinishReport,
+};
+
+#[cfg(feature = "embedder")]
+pub use embed::target::{TargetIsize, TargetUsize};
 
 #[cfg(feature = "store")]
 pub use store::private as __private;
 
+#[cfg(feature = "store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "store")))]
+pub use lookup::{get_by_name, get_raw};
+
+#[cfg(all(fuzzing, feature = "embedder"))]
+#[doc(hidden)]
+pub use embed::embedder::fuzz as __fuzz;
+
 /// A handle to a binary executable file that linkstore can use.
 ///
 /// ## Implementors
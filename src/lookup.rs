@@ -0,0 +1,150 @@
+//! Runtime lookup of linkstore values by a name which is only known at runtime.
+//!
+//! The first lookup indexes every linkstore in the loaded binary, using the same container parser as the [`Embedder`](crate::Embedder).
+//! `linkstore!` registers each container in a registry section, whose bounds are found through symbols defined by the linker.
+//! WebAssembly modules can't place statics in sections at runtime, so their data is scanned instead.
+
+use crate::{
+	embed::{container, encode::POOL_NAMESPACE},
+	pool, TryDecodeLinkstore,
+};
+use std::{collections::HashMap, sync::OnceLock};
+
+#[cfg(not(target_family = "wasm"))]
+use crate::store::private::RegistryEntry;
+
+static INDEX: OnceLock<HashMap<String, Box<[u8]>>> = OnceLock::new();
+
+/// Gets the bytes of a linkstore in the current binary by its fully qualified name.
+///
+/// Returns `None` if the binary doesn't contain a linkstore with this name.
+///
+/// Linkstores are looked up in the executable or library which linkstore is linked into, so a plugin finds its own keys rather than its host's.
+/// Keys inserted into the binary's pool with [`Embedder::insert`](crate::Embedder::insert) are found too.
+/// If a key occurs more than once, the first occurrence is returned.
+///
+/// ```no_run
+/// match linkstore::get_raw("mycrate::app.net.port") {
+///     Some(bytes) => println!("port is stored in {} bytes", bytes.len()),
+///     None => println!("no port"),
+/// }
+/// ```
+pub fn get_raw(name: &str) -> Option<&'static [u8]> {
+	INDEX.get_or_init(|| index(regions())).get(name).map(AsRef::as_ref)
+}
+
+/// Decodes a linkstore in the current executable by its fully qualified name.
+///
+/// Returns `None` if the executable doesn't contain a linkstore with this name, if its size doesn't match the size of `T`, or if it fails to decode.
+///
+/// ## Safety
+///
+/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
+pub unsafe fn get_by_name<T: TryDecodeLinkstore>(name: &str) -> Option<T> {
+	get_raw(name)
+		.filter(|bytes| bytes.len() == core::mem::size_of::<T>())
+		.and_then(|bytes| T::try_from_le_bytes(bytes).ok())
+}
+
+/// Indexes the linkstores in regions of memory, including keys inserted into a pool.
+pub(crate) fn index<R: AsRef<[u8]>>(regions: impl IntoIterator<Item = R>) -> HashMap<String, Box<[u8]>> {
+	let mut index = HashMap::new();
+
+	for region in regions {
+		for (_, container) in container::scan(region.as_ref(), core::mem::size_of::<usize>()) {
+			// Keys inserted into a pool are packed inside its value, which scanning skips over
+			let name = String::from_utf8_lossy(container.name);
+			if name.rsplit_once("::").is_some_and(|(namespace, _)| namespace == POOL_NAMESPACE) {
+				for entry in pool::entries(container.value) {
+					index
+						.entry(String::from_utf8_lossy(entry.name).into_owned())
+						.or_insert_with(|| entry.value.into());
+				}
+			}
+
+			index.entry(name.into_owned()).or_insert_with(|| container.value.into());
+		}
+	}
+
+	index
+}
+
+/// Copies each registered container out of the loaded binary.
+#[cfg(not(target_family = "wasm"))]
+fn regions() -> Vec<Vec<u8>> {
+	registry().iter().filter_map(RegistryEntry::read).collect()
+}
+
+/// Copies the module's data out of linear memory, which contains every container.
+#[cfg(target_family = "wasm")]
+fn regions() -> Vec<Vec<u8>> {
+	extern "C" {
+		// Defined by `wasm-ld` at the start and end of the module's data
+		static __global_base: u8;
+		static __data_end: u8;
+	}
+
+	// Taking the address of an extern static only needs `unsafe` before Rust 1.82
+	#[allow(unused_unsafe)]
+	let (start, end) = unsafe { (core::ptr::addr_of!(__global_base), core::ptr::addr_of!(__data_end)) };
+	let data = (0..(end as usize).saturating_sub(start as usize))
+		.map(|i| unsafe { core::ptr::read_volatile(start.add(i)) })
+		.collect();
+	vec![data]
+}
+
+/// Returns the entries between two bounds of the registry.
+#[cfg(not(target_family = "wasm"))]
+unsafe fn entries(start: *const RegistryEntry, stop: *const RegistryEntry) -> &'static [RegistryEntry] {
+	core::slice::from_raw_parts(start, usize::try_from(stop.offset_from(start)).unwrap_or_default())
+}
+
+/// Returns the registry, whose bounds are defined by the linker for sections named like C identifiers.
+#[cfg(not(any(target_os = "macos", windows, target_family = "wasm")))]
+fn registry() -> &'static [RegistryEntry] {
+	extern "C" {
+		#[link_name = "__start_linkstore_registry"]
+		static START: RegistryEntry;
+		#[link_name = "__stop_linkstore_registry"]
+		static STOP: RegistryEntry;
+	}
+
+	// The bounds are only defined if the section exists, so it always contains this entry
+	#[link_section = "linkstore_registry"]
+	#[used]
+	static EMPTY: RegistryEntry = RegistryEntry::EMPTY;
+
+	unsafe { entries(core::ptr::addr_of!(START), core::ptr::addr_of!(STOP)) }
+}
+
+/// Returns the registry, whose bounds are defined by the linker for every section.
+#[cfg(target_os = "macos")]
+fn registry() -> &'static [RegistryEntry] {
+	extern "C" {
+		#[link_name = "\x01section$start$__DATA$__lnkstre_reg"]
+		static START: RegistryEntry;
+		#[link_name = "\x01section$end$__DATA$__lnkstre_reg"]
+		static STOP: RegistryEntry;
+	}
+
+	// The bounds are only defined if the section exists, so it always contains this entry
+	#[link_section = "__DATA,__lnkstre_reg"]
+	#[used]
+	static EMPTY: RegistryEntry = RegistryEntry::EMPTY;
+
+	unsafe { entries(core::ptr::addr_of!(START), core::ptr::addr_of!(STOP)) }
+}
+
+/// Returns the registry, which the linker sorts between these bounds because grouped sections are ordered by the name after the `$`.
+#[cfg(windows)]
+fn registry() -> &'static [RegistryEntry] {
+	#[link_section = ".lnkreg$a"]
+	#[used]
+	static START: RegistryEntry = RegistryEntry::EMPTY;
+
+	#[link_section = ".lnkreg$z"]
+	#[used]
+	static STOP: RegistryEntry = RegistryEntry::EMPTY;
+
+	unsafe { entries(core::ptr::addr_of!(START).add(1), core::ptr::addr_of!(STOP)) }
+}
@@ -0,0 +1,61 @@
+//! Runtime access to keys inserted into the binary's pool after compilation.
+//!
+//! A pool is a block of space reserved with [`linkstore_pool!`](crate::linkstore_pool), into which [`Embedder::insert`](crate::Embedder::insert)
+//! allocates new keys, so that metadata can be attached to a binary without rebuilding it.
+//!
+//! ```no_run
+//! #[macro_use] extern crate linkstore;
+//!
+//! linkstore_pool!(POOL, 4096);
+//!
+//! fn main() {
+//!     match unsafe { linkstore::pool::get::<u32>("build.number") } {
+//!         Some(build) => println!("build #{build}"),
+//!         None => println!("unknown build"),
+//!     }
+//! }
+//! ```
+
+use crate::{
+	embed::container::{self, Container},
+	store::private::PoolRef,
+	DecodeLinkstore,
+};
+use core::mem::size_of;
+
+extern "Rust" {
+	// Defined by `linkstore_pool!`
+	static __LINKSTORE_POOL: PoolRef;
+}
+
+/// Decodes a key inserted into this binary's pool.
+///
+/// Returns `None` if the key hasn't been inserted, or if its size doesn't match the size of `T`.
+///
+/// The binary must define a pool with [`linkstore_pool!`](crate::linkstore_pool), otherwise it will fail to link.
+///
+/// ## Safety
+///
+/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
+pub unsafe fn get<T: DecodeLinkstore>(name: &str) -> Option<T> {
+	let pool = __LINKSTORE_POOL.read();
+	find(&pool, name).filter(|value| value.len() == size_of::<T>()).map(T::from_le_bytes)
+}
+
+/// Finds the value of a key in the contents of a pool.
+pub(crate) fn find<'p>(pool: &'p [u8], name: &str) -> Option<&'p [u8]> {
+	entries(pool)
+		.find(|container| container.name == name.as_bytes())
+		.map(|container| container.value)
+}
+
+/// Iterates over the keys inserted into the contents of a pool.
+///
+/// Keys are packed one after another from the start of the pool, so iteration stops at the first byte which isn't a valid linkstore.
+pub(crate) fn entries(mut pool: &[u8]) -> impl Iterator<Item = Container<'_>> {
+	core::iter::from_fn(move || {
+		let container = container::parse(pool, size_of::<usize>())?;
+		pool = &pool[container.end()..];
+		Some(container)
+	})
+}
@@ -4,6 +4,10 @@ pub mod private {
 	use core::{cell::UnsafeCell, mem::size_of};
 
 	pub use crate::embed::encode::MAGIC;
+	use crate::embed::encode::{header_checksum, MAX_NAME_LEN};
+
+	#[cfg(feature = "env-override")]
+	pub use crate::env_override::*;
 
 	#[repr(transparent)]
 	pub struct VolatileWrapper<T: Sized>(UnsafeCell<T>);
@@ -20,15 +24,118 @@ pub mod private {
 				&*self.0.get()
 			}
 		}
+
+		/// Copies the bytes of the value without interpreting them, since they may have been modified after compilation.
+		#[doc(hidden)]
+		pub fn read_bytes(&'static self) -> Vec<u8> {
+			let bytes = self.0.get() as *const u8;
+			(0..size_of::<T>()).map(|i| unsafe { core::ptr::read_volatile(bytes.add(i)) }).collect()
+		}
 	}
 	unsafe impl<T: TryDecodeLinkstore + Sized> Sync for VolatileWrapper<T> {}
 
-	pub const fn calc_padding<Container, T>(name: &'static str) -> usize
-	where
-		Container: Sized,
-		T: Sized,
-	{
-		size_of::<Container>() - (name.len() + 1 + 1) - (size_of::<usize>() * 2) - size_of::<T>()
+	/// Points to the contents of the pool defined with `linkstore_pool!`.
+	pub struct PoolRef {
+		bytes: *const u8,
+		len: usize,
+	}
+	impl PoolRef {
+		#[doc(hidden)]
+		pub const fn new<const N: usize>(pool: &'static VolatileWrapper<[u8; N]>) -> Self {
+			Self {
+				bytes: pool.0.get() as *const u8,
+				len: N,
+			}
+		}
+
+		/// Copies the contents of the pool, which may have been modified after compilation.
+		pub(crate) fn read(&self) -> Vec<u8> {
+			(0..self.len).map(|i| unsafe { core::ptr::read_volatile(self.bytes.add(i)) }).collect()
+		}
+	}
+	unsafe impl Sync for PoolRef {}
+
+	/// Points to a linkstore's container, so that [`get_raw`](crate::get_raw) can find every linkstore in the loaded binary without reading it from disk.
+	///
+	/// `linkstore!` places an entry for each container in a registry section, which the linker gathers into one array.
+	#[cfg(not(target_family = "wasm"))]
+	#[repr(C)]
+	pub struct RegistryEntry {
+		container: *const u8,
+		len: usize,
+	}
+	#[cfg(not(target_family = "wasm"))]
+	impl RegistryEntry {
+		/// An entry which doesn't point to any container, which marks the bounds of the registry.
+		pub(crate) const EMPTY: Self = Self {
+			container: core::ptr::null(),
+			len: 0,
+		};
+
+		#[doc(hidden)]
+		pub const fn new<C>(container: &'static C) -> Self {
+			Self {
+				container: container as *const C as *const u8,
+				len: size_of::<C>(),
+			}
+		}
+
+		/// Copies the bytes of the container, which may have been modified after compilation.
+		///
+		/// Returns `None` for empty entries, which linkers may also insert as padding.
+		pub(crate) fn read(&self) -> Option<Vec<u8>> {
+			if self.container.is_null() {
+				return None;
+			}
+			Some(
+				(0..self.len)
+					.map(|i| unsafe { core::ptr::read_volatile(self.container.add(i)) })
+					.collect(),
+			)
+		}
+	}
+	#[cfg(not(target_family = "wasm"))]
+	unsafe impl Sync for RegistryEntry {}
+
+	/// The name at the start of a linkstore's container, which is the magic bytes followed by the nul-terminated `name`.
+	pub const fn container_name<const N: usize>(name: &'static str) -> [u8; N] {
+		assert!(name.len() <= MAX_NAME_LEN, "linkstore key names must be at most 1024 bytes long");
+
+		let mut static_bytes = [0u8; N];
+
+		let mut i = 0;
+		while i < MAGIC.len() {
+			static_bytes[i] = MAGIC[i];
+			i += 1;
+		}
+
+		let bytes = name.as_bytes();
+		let mut i = 0;
+		while i < bytes.len() {
+			static_bytes[MAGIC.len() + i] = bytes[i];
+			i += 1;
+		}
+
+		static_bytes
+	}
+
+	/// The size of the header of a linkstore named `name`, which is followed by its padding and value.
+	pub const fn header_size(name: &'static str) -> usize {
+		(MAGIC.len() + name.len() + 1) + (size_of::<usize>() * 2) + size_of::<u32>()
+	}
+
+	/// The padding between the header and value of a container whose value is `value_offset` bytes from its start.
+	pub const fn calc_padding(name: &'static str, value_offset: usize) -> usize {
+		value_offset - header_size(name)
+	}
+
+	pub const fn calc_checksum<T: Sized>(name: &'static str, value_offset: usize) -> [u8; 4] {
+		header_checksum(
+			name.as_bytes(),
+			&size_of::<T>().to_le_bytes(),
+			&calc_padding(name, value_offset).to_le_bytes(),
+		)
+		.to_le_bytes()
 	}
 }
 
@@ -55,58 +162,400 @@ pub mod private {
 ///     }
 /// }
 /// ```
+///
+/// ## Reading values
+///
+/// Each linkstore's module contains a safe `try_get()`, which decodes the value with [`TryDecodeLinkstore`](crate::TryDecodeLinkstore), validating the bytes in the binary.
+///
+/// The unsafe `get()` returns a reference to the value in place, without any validation, so it can only be called for types implementing [`AnyBitPattern`](crate::AnyBitPattern),
+/// such as numbers and arrays of numbers. A `bool` which has been patched to `2` must be read with `try_get()`.
+///
+/// ```
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore! {
+///     pub static LINKSTORE_ENABLED: bool = true;
+///     pub static LINKSTORE_PORT: u16 = 8080;
+/// }
+///
+/// fn main() {
+///     assert_eq!(LINKSTORE_ENABLED::try_get(), Ok(true));
+///     assert_eq!(unsafe { *LINKSTORE_PORT::get() }, 8080);
+/// }
+/// ```
+///
+/// ## Key names
+///
+/// By default, the name of a linkstore's key is the name of the static. Use `#[linkstore(name = "...")]` to give it a different key name.
+///
+/// To prevent collisions between crates that define linkstores with the same name, keys can be prefixed with a namespace by starting the invocation with `namespace = "...";`.
+/// The fully qualified name of a namespaced key is `namespace::name`, and is available as the `NAME` constant in the linkstore's module.
+///
+/// ```
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore! {
+///     namespace = "mycrate";
+///
+///     #[linkstore(name = "app.net.port")]
+///     pub static PORT: u16 = 8080;
+///
+///     pub static LINKSTORE_VERSION: u32 = 1;
+/// }
+///
+/// fn main() {
+///     assert_eq!(PORT::NAME, "mycrate::app.net.port");
+///     assert_eq!(LINKSTORE_VERSION::NAME, "mycrate::LINKSTORE_VERSION");
+/// }
+/// ```
+///
+/// ## Section names
+///
+/// By default, linkstores are placed in the `.lnkstre` section (`__TEXT,.lnkstre` on macOS). Use `section = "...";` to place them in a different section,
+/// and `macho_section = "segment,section";` to override the segment and section used on macOS.
+///
+/// Binaries using a custom section name must be opened with [`EmbedderBuilder::section_name`](crate::EmbedderBuilder::section_name).
+///
+/// ```no_run
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore! {
+///     namespace = "mycrate";
+///     section = ".mycfg";
+///     macho_section = "__DATA,__mycfg";
+///
+///     pub static LINKSTORE_VERSION: u32 = 1;
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ## Default values
+///
+/// Starting the invocation with `keep_defaults = true;` keeps an untouched copy of each value as it was compiled, in a second key named `linkstore::default::<NAME>`,
+/// which is available as the `DEFAULT_NAME` constant in the linkstore's module. This doubles the space taken up by the values.
+///
+/// The linkstore's module then contains `is_default()`, which returns whether the value has been modified since compilation.
+/// The [`Embedder`](crate::Embedder) can read the default with [`default_value`](crate::Embedder::default_value), compare against it with
+/// [`is_modified`](crate::Embedder::is_modified) and restore it with [`reset`](crate::Embedder::reset).
+///
+/// ```
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore! {
+///     namespace = "mycrate";
+///     keep_defaults = true;
+///
+///     pub static LINKSTORE_VERSION: u32 = 1;
+/// }
+///
+/// fn main() {
+///     assert_eq!(LINKSTORE_VERSION::DEFAULT_NAME, "linkstore::default::mycrate::LINKSTORE_VERSION");
+///     assert!(LINKSTORE_VERSION::is_default());
+/// }
+/// ```
+///
+/// ## Environment variable overrides
+///
+/// With the `env-override` feature enabled, the first call to a linkstore's `get` or `try_get` checks the `LINKSTORE_<NAME>` environment variable,
+/// where `<NAME>` is the key's fully qualified name in uppercase with any other characters replaced with underscores,
+/// and returns its value instead of the one embedded in the binary.
+///
+/// Numbers are parsed from decimal (or hex prefixed with `0x` for integers), `bool`s from `true`, `false`, `1` or `0`, and arrays from their elements separated by commas.
+/// Byte arrays can also be parsed from hex. Keys of other types are never overridden, and variables which are set for them or which can't be parsed are reported on stderr and ignored.
+///
+/// Overrides are only compiled into crates built with debug assertions, so release builds are unaffected even with the feature enabled.
+///
+/// ```no_run
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore! {
+///     namespace = "mycrate";
+///
+///     #[linkstore(name = "app.net.port")]
+///     pub static PORT: u16 = 8080;
+/// }
+///
+/// fn main() {
+///     // LINKSTORE_MYCRATE__APP_NET_PORT=9090 cargo run
+///     println!("port = {}", unsafe { PORT::get() });
+/// }
+/// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "store")))]
 #[macro_export]
 macro_rules! linkstore {
-	{$($vis:vis static $name:ident: $ty:ty = $init:expr;)+} => {$(
+	(@name [] [] $name:ident) => {
+		stringify!($name)
+	};
+	(@name [] [$key:literal] $name:ident) => {
+		$key
+	};
+	(@name [$namespace:literal] [] $name:ident) => {
+		concat!($namespace, "::", stringify!($name))
+	};
+	(@name [$namespace:literal] [$key:literal] $name:ident) => {
+		concat!($namespace, "::", $key)
+	};
+
+	(@section []) => {
+		".lnkstre"
+	};
+	(@section [$section:literal]) => {
+		$section
+	};
+	(@macho_section [] []) => {
+		"__TEXT,.lnkstre"
+	};
+	(@macho_section [$section:literal] []) => {
+		concat!("__TEXT,", $section)
+	};
+	(@macho_section $section:tt [$macho_section:literal]) => {
+		$macho_section
+	};
+
+	(@container [$section:tt $macho_section:tt] $static:ident: $container:ident($name:ident) = $init:expr) => {
+		// The embedder finds the value from the size and padding in the header, so the value must directly follow the padding.
+		const _: () = {
+			let value_offset = ::core::mem::offset_of!($container, value);
+			assert!(align_of::<$container>() == align_of::<ValueType>(), "Alignment error - this is a bug with linkstore!");
+			assert!(value_offset >= header_size($name), "Layout error - this is a bug with linkstore!");
+			assert!(size_of::<$container>() == value_offset + size_of::<ValueType>(), "Layout error - this is a bug with linkstore!");
+		};
+
+		// On WebAssembly, statics with a `link_section` are emitted as custom sections, which can't be read at runtime,
+		// so the container is left in the module's data segments instead.
+		#[cfg_attr(target_os = "macos", link_section = $crate::linkstore!(@macho_section $section $macho_section))]
+		#[cfg_attr(not(any(target_os = "macos", target_family = "wasm")), link_section = $crate::linkstore!(@section $section))]
+		// The embedder searches symbol tables for names containing `__LINKSTORE_CONTAINER`, so it must not be changed.
+		#[used]
+		#[doc(hidden)]
+		pub(super) static $static: $container = LinkStoreContainer {
+			name: container_name($name),
+			size: size_of::<ValueType>().to_le_bytes(),
+			padding: calc_padding($name, ::core::mem::offset_of!($container, value)).to_le_bytes(),
+			checksum: calc_checksum::<ValueType>($name, ::core::mem::offset_of!($container, value)),
+			value: VolatileWrapper::new($init)
+		};
+
+		// `linkstore::get_raw` finds the container through this entry. The registry sections must match the bounds in `lookup.rs`.
+		#[cfg(not(target_family = "wasm"))]
+		const _: () = {
+			#[cfg_attr(target_os = "macos", link_section = "__DATA,__lnkstre_reg")]
+			#[cfg_attr(windows, link_section = ".lnkreg$m")]
+			#[cfg_attr(not(any(target_os = "macos", windows)), link_section = "linkstore_registry")]
+			#[used]
+			static REGISTRY_ENTRY: $crate::__private::RegistryEntry = $crate::__private::RegistryEntry::new(&$static);
+		};
+	};
+
+	(@default [] $options:tt $key:tt $name:ident = $init:expr) => {};
+	(@default [true] [$namespace:tt $section:tt $macho_section:tt] $key:tt $name:ident = $init:expr) => {
+		/// The fully qualified name of the key holding this linkstore's default value.
+		pub const DEFAULT_NAME: &'static str = concat!("linkstore::default::", $crate::linkstore!(@name $namespace $key $name));
+
+		type DefaultContainer = LinkStoreContainer<ValueType, { MAGIC.len() + DEFAULT_NAME.len() + 1 }>;
+
+		// An untouched copy of the value as it was compiled, which the embedder never modifies.
+		$crate::linkstore!(@container [$section $macho_section] __LINKSTORE_CONTAINER_DEFAULT: DefaultContainer(DEFAULT_NAME) = $init);
+
+		/// Returns whether the value in the binary is the value it was compiled with, comparing their bytes.
+		///
+		/// Environment variable overrides are not taken into account.
+		#[allow(dead_code)]
+		pub fn is_default() -> bool {
+			__LINKSTORE_CONTAINER.value.read_bytes() == __LINKSTORE_CONTAINER_DEFAULT.value.read_bytes()
+		}
+	};
+
+	(@items [$namespace:tt $section:tt $macho_section:tt $defaults:tt] $(
+		$(#[linkstore(name = $key:literal)])?
+		$vis:vis static $name:ident: $ty:ty = $init:expr;
+	)+) => {$(
 		#[allow(non_snake_case)]
 		$vis mod $name {
 			use ::core::mem::{size_of, align_of};
-			use $crate::__private::*;
+			use $crate::__private::{calc_checksum, calc_padding, container_name, header_size, VolatileWrapper, MAGIC};
+
+			// Types and initializers are written in the scope of the parent module
+			#[allow(unused_imports)]
+			use super::*;
 
-			const NAME: &'static str = stringify!($name);
+			/// The fully qualified name of this linkstore's key.
+			pub const NAME: &'static str = $crate::linkstore!(@name $namespace [$($key)?] $name);
 
 			#[repr(C)]
-			pub struct LinkStoreContainer<T: $crate::EncodeLinkstore> {
-				name: [u8; NAME.len() + 1 + 1],
+			pub struct LinkStoreContainer<T: $crate::EncodeLinkstore, const N: usize> {
+				name: [u8; N],
 				size: [u8; size_of::<usize>()],
 				padding: [u8; size_of::<usize>()],
+				checksum: [u8; 4],
 				pub value: VolatileWrapper<T>
 			}
 
-			#[cfg_attr(target_os = "macos", link_section = "__TEXT,.lnkstre")]
-			#[cfg_attr(not(target_os = "macos"), link_section = ".lnkstre")]
-			#[used]
-			static $name: LinkStoreContainer<$ty> = LinkStoreContainer {
-				name: {
-					let mut static_bytes = [0u8; NAME.len() + 1 + 1];
-					static_bytes[0] = MAGIC;
-
-					let bytes = NAME.as_bytes();
-					let mut i = 0;
-					while i < NAME.len() {
-						static_bytes[i + 1] = bytes[i];
-						i += 1;
-					}
+			type ValueType = $ty;
+			type Container = LinkStoreContainer<ValueType, { MAGIC.len() + NAME.len() + 1 }>;
 
-					static_bytes
-				},
+			$crate::linkstore!(@container [$section $macho_section] __LINKSTORE_CONTAINER: Container(NAME) = $init);
 
-				size: size_of::<$ty>().to_le_bytes(),
-				padding: calc_padding::<LinkStoreContainer<$ty>, $ty>(NAME).to_le_bytes(),
+			$crate::linkstore!(@default $defaults [$namespace $section $macho_section] [$($key)?] $name = $init);
 
-				value: VolatileWrapper::new($init)
-			};
+			/// Returns this linkstore's override from the environment, if enabled.
+			#[inline(always)]
+			fn env_override() -> Option<&'static $ty> {
+				$crate::__linkstore_env_override!(NAME, $ty)
+			}
 
-			/// Gets a the value contained in the linkstore.
+			/// Gets a reference to the value contained in the linkstore.
+			///
+			/// Can only be called for types implementing [`AnyBitPattern`]($crate::AnyBitPattern). Other types must be read with [`try_get`].
 			///
 			/// ## Safety
 			///
-			/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary or unsound decoding implementations may cause undefined behavior.
-			pub unsafe fn get() -> &'static $ty {
-				debug_assert_eq!(align_of::<LinkStoreContainer<$ty>>(), align_of::<$ty>(), "Alignment error - this is a bug with linkstore!");
-				$name.value.get()
+			/// This function is unsafe because malformed, corrupted or otherwise invalid data in the binary may cause undefined behavior.
+			#[allow(dead_code)]
+			pub unsafe fn get() -> &'static $ty
+			where
+				// The bound is higher-ranked so that it's only checked when `get` is called, rather than failing to compile for other types.
+				for<'a> $ty: $crate::AnyBitPattern,
+			{
+				if let Some(value) = env_override() {
+					return value;
+				}
+				__LINKSTORE_CONTAINER.value.get()
+			}
+
+			/// Decodes the value contained in the linkstore, validating the bytes in the binary with [`TryDecodeLinkstore`]($crate::TryDecodeLinkstore).
+			#[allow(dead_code)]
+			pub fn try_get() -> Result<$ty, <$ty as $crate::TryDecodeLinkstore>::Error> {
+				if let Some(value) = env_override() {
+					return <$ty as $crate::TryDecodeLinkstore>::try_from_le_bytes(&$crate::EncodeLinkstore::as_le_bytes(value));
+				}
+				<$ty as $crate::TryDecodeLinkstore>::try_from_le_bytes(&__LINKSTORE_CONTAINER.value.read_bytes())
 			}
 		}
 	)+};
+
+	(@options [$namespace:tt $section:tt $macho_section:tt $defaults:tt] namespace = $value:literal; $($tt:tt)+) => {
+		$crate::linkstore!(@options [[$value] $section $macho_section $defaults] $($tt)+);
+	};
+	(@options [$namespace:tt $section:tt $macho_section:tt $defaults:tt] section = $value:literal; $($tt:tt)+) => {
+		$crate::linkstore!(@options [$namespace [$value] $macho_section $defaults] $($tt)+);
+	};
+	(@options [$namespace:tt $section:tt $macho_section:tt $defaults:tt] macho_section = $value:literal; $($tt:tt)+) => {
+		$crate::linkstore!(@options [$namespace $section [$value] $defaults] $($tt)+);
+	};
+	(@options [$namespace:tt $section:tt $macho_section:tt $defaults:tt] keep_defaults = true; $($tt:tt)+) => {
+		$crate::linkstore!(@options [$namespace $section $macho_section [true]] $($tt)+);
+	};
+	(@options [$namespace:tt $section:tt $macho_section:tt $defaults:tt] keep_defaults = false; $($tt:tt)+) => {
+		$crate::linkstore!(@options [$namespace $section $macho_section []] $($tt)+);
+	};
+	(@options $options:tt $($items:tt)+) => {
+		$crate::linkstore!(@items $options $($items)+);
+	};
+
+	{$($tt:tt)+} => {
+		$crate::linkstore!(@options [[] [] [] []] $($tt)+);
+	};
+}
+
+/// Returns a key's override from the environment, if the `env-override` feature is enabled and the crate defining the key is built with debug assertions.
+#[cfg(feature = "env-override")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linkstore_env_override {
+	($name:expr, $ty:ty) => {{
+		#[cfg(debug_assertions)]
+		let env_override = {
+			#[allow(unused_imports)]
+			use $crate::__private::{NoEnvOverrideKind as _, ParseEnvOverrideKind as _};
+
+			static ENV_OVERRIDE: $crate::__private::EnvOverrideCell<$ty> = $crate::__private::EnvOverrideCell::new();
+			ENV_OVERRIDE.get_or_init(|| (&&$crate::__private::EnvOverride::<$ty>::new()).env_override($name))
+		};
+
+		#[cfg(not(debug_assertions))]
+		let env_override = None;
+
+		env_override
+	}};
+}
+
+#[cfg(not(feature = "env-override"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linkstore_env_override {
+	($name:expr, $ty:ty) => {
+		None
+	};
+}
+
+/// Reserves space in the current binary for keys inserted after compilation.
+///
+/// The pool is `SIZE` bytes of zeroes, into which [`Embedder::insert`](crate::Embedder::insert) allocates new keys.
+/// Each key takes up the size of its header and value, and the header is the same as a `linkstore!` key's.
+///
+/// Inserted keys can be read at runtime with [`linkstore::pool::get`](crate::pool::get).
+///
+/// ```no_run
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore_pool!(POOL, 4096);
+///
+/// fn main() {
+///     let build = unsafe { linkstore::pool::get::<u32>("build.number") };
+///     println!("build {build:?}");
+/// }
+/// ```
+///
+/// A binary can only contain one pool, including pools defined by its dependencies, so libraries should leave defining it to the final binary.
+/// Its key is `linkstore::pool::NAME`, and the `linkstore::pool` namespace is reserved for pools.
+#[cfg_attr(docsrs, doc(cfg(feature = "store")))]
+#[macro_export]
+macro_rules! linkstore_pool {
+	($vis:vis $name:ident, $size:expr) => {
+		$crate::linkstore! {
+			namespace = "linkstore::pool";
+
+			$vis static $name: [u8; $size] = [0; $size];
+		}
+
+		// `linkstore::pool::get` finds the pool through this symbol, which is why there can only be one pool.
+		#[no_mangle]
+		static __LINKSTORE_POOL: $crate::__private::PoolRef = $crate::__private::PoolRef::new(&$name::__LINKSTORE_CONTAINER.value);
+	};
+}
+
+/// Defines a provenance record in the current binary, which records the tool that last stamped it, when, and with which values.
+///
+/// The record is a [`Provenance`](crate::Provenance), which [`Embedder::finish`](crate::Embedder::finish) fills in every time the binary is stamped.
+/// The tool identifier is given to [`EmbedderBuilder::tool`](crate::EmbedderBuilder::tool), and the record can be read back with
+/// [`Embedder::provenance`](crate::Embedder::provenance), or at runtime through the linkstore's module.
+///
+/// ```
+/// #[macro_use] extern crate linkstore;
+///
+/// linkstore_provenance!(PROVENANCE);
+///
+/// fn main() {
+///     let provenance = PROVENANCE::try_get().unwrap();
+///     if provenance.is_stamped() {
+///         println!("stamped by {} at {}", provenance.tool(), provenance.timestamp());
+///     } else {
+///         println!("not stamped");
+///     }
+/// }
+/// ```
+///
+/// Its key is `linkstore::provenance::$name`, and the `linkstore::provenance` namespace is reserved for provenance records.
+/// Libraries should leave defining it to the final binary, as every record in a binary is filled in with the same values.
+#[cfg_attr(docsrs, doc(cfg(feature = "store")))]
+#[macro_export]
+macro_rules! linkstore_provenance {
+	($vis:vis $name:ident) => {
+		$crate::linkstore! {
+			namespace = "linkstore::provenance";
+
+			$vis static $name: $crate::Provenance = $crate::Provenance::UNSTAMPED;
+		}
+	};
 }
@@ -0,0 +1,786 @@
+//! Generates minimal binaries containing linkstores, so that stamping pipelines can be tested without compiling real binaries.
+//!
+//! Requires the `testing` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use linkstore::{testing::SyntheticBinary, Embedder, Format, PointerWidth};
+//!
+//! let mut binary = SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+//!     .entry("LINKSTORE_TEST", &0xDEADBEEF_u64)
+//!     .entry("LINKSTORE_YEAH", &0xDEADBEEF_u32)
+//!     .build();
+//!
+//! let mut cursor = std::io::Cursor::new(&mut binary[..]);
+//! let mut embedder = Embedder::new(&mut cursor).unwrap();
+//! embedder.embed("LINKSTORE_TEST", &69_u64).unwrap();
+//! embedder.finish().unwrap();
+//!
+//! let expected = SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+//!     .entry("LINKSTORE_TEST", &69_u64)
+//!     .entry("LINKSTORE_YEAH", &0xDEADBEEF_u32)
+//!     .build();
+//! assert_eq!(binary, expected);
+//! ```
+
+use crate::{
+	embed::encode::{encode_header, MAGIC},
+	EncodeLinkstore, Format, PointerWidth,
+};
+
+const SECTION_NAME: &str = ".lnkstre";
+const SECTION_ALIGN: usize = 16;
+
+#[derive(Debug, Clone)]
+struct Entry {
+	name: String,
+	bytes: Vec<u8>,
+	align: usize,
+}
+
+/// A container within the generated linkstore section.
+struct Container {
+	symbol: String,
+	offset: u64,
+	size: u64,
+}
+
+/// Builds a minimal but valid binary with a `.lnkstre` section, or one named with [`SyntheticBinary::section_name`], containing linkstores in the exact layout emitted by `linkstore!`.
+///
+/// | Format | Produces |
+/// |---|---|
+/// | [`Format::Elf`] | An x86 or x86-64 ELF executable |
+/// | [`Format::Pe`] | An x86 or x86-64 PE image |
+/// | [`Format::Coff`] | An ar archive containing an x86 or x86-64 COFF object, as found in MSVC static libraries |
+/// | [`Format::MachO`] | An x86 or x86-64 Mach-O executable, or a fat binary with an ARM slice too |
+/// | [`Format::Wasm`] | A WebAssembly module with the linkstores in a data segment |
+///
+/// Building the same binary with different values of the same size produces a binary which differs only in those values,
+/// so the output of [`Embedder`](crate::Embedder) can be compared against an expected binary byte for byte.
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct SyntheticBinary {
+	format: Format,
+	pointer_width: PointerWidth,
+	entries: Vec<Entry>,
+	symbols: bool,
+	archive: bool,
+	fat: bool,
+	signed: bool,
+	section_name: String,
+}
+impl SyntheticBinary {
+	/// Creates a new [`SyntheticBinary`] with no linkstores.
+	pub fn new(format: Format, pointer_width: PointerWidth) -> Self {
+		Self {
+			format,
+			pointer_width,
+			entries: Vec::new(),
+			symbols: false,
+			archive: false,
+			fat: false,
+			signed: false,
+			section_name: SECTION_NAME.to_owned(),
+		}
+	}
+
+	/// Adds a linkstore, aligned as `T` would be.
+	pub fn entry<T: EncodeLinkstore>(self, name: &str, value: &T) -> Self {
+		let bytes = value.as_le_bytes().into_owned();
+		self.entry_bytes(name, bytes, core::mem::align_of::<T>())
+	}
+
+	/// Adds a linkstore containing the given bytes, aligned to `align` bytes.
+	pub fn entry_bytes(mut self, name: &str, bytes: impl Into<Vec<u8>>, align: usize) -> Self {
+		self.entries.push(Entry {
+			name: name.to_owned(),
+			bytes: bytes.into(),
+			align: align.max(1),
+		});
+		self
+	}
+
+	/// Adds a symbol for each linkstore, as an unstripped binary would have.
+	///
+	/// Has no effect on WebAssembly modules, which have no symbol table.
+	pub fn symbols(mut self, symbols: bool) -> Self {
+		self.symbols = symbols;
+		self
+	}
+
+	/// Wraps the binary in an ar archive as a relocatable object, as a static library would be.
+	///
+	/// Has no effect on [`Format::Coff`], which is always wrapped in an archive.
+	pub fn archive(mut self, archive: bool) -> Self {
+		self.archive = archive;
+		self
+	}
+
+	/// Builds a fat Mach-O binary containing a slice for both an x86 and an ARM architecture, so every linkstore occurs twice.
+	///
+	/// Has no effect on formats other than [`Format::MachO`].
+	pub fn fat(mut self, fat: bool) -> Self {
+		self.fat = fat;
+		self
+	}
+
+	/// Adds a placeholder code signature, as an Authenticode certificate table for [`Format::Pe`], or an `LC_CODE_SIGNATURE` load command for [`Format::MachO`].
+	///
+	/// The signature isn't valid, but is laid out as a signing tool would lay it out. Has no effect on other formats.
+	pub fn signed(mut self, signed: bool) -> Self {
+		self.signed = signed;
+		self
+	}
+
+	/// Places the linkstores in a section with the given name instead of `.lnkstre`, as `linkstore!` does with `section = "...";`.
+	///
+	/// Names of the form `segment,section` place the section in that segment of a Mach-O binary, which otherwise places it in `__TEXT`.
+	/// PE images truncate names to 8 bytes, whilst COFF objects keep longer names in their string table. Has no effect on WebAssembly modules.
+	pub fn section_name(mut self, name: &str) -> Self {
+		self.section_name = name.to_owned();
+		self
+	}
+
+	/// Builds the binary.
+	#[must_use]
+	pub fn build(&self) -> Vec<u8> {
+		let object = self.archive || self.format == Format::Coff;
+		let binary = match self.format {
+			Format::Elf => self.elf(object),
+			Format::Pe => self.pe(),
+			Format::Coff => self.coff(),
+			Format::MachO if self.fat => self.fat_macho(object),
+			Format::MachO => self.macho(self.mach_cputypes()[0], object),
+			Format::Wasm => self.wasm(),
+		};
+
+		// COFF objects are only found in static libraries
+		if object {
+			let name = if self.format == Format::Coff { "fixture.obj" } else { "fixture.o" };
+			archive(name, &binary)
+		} else {
+			binary
+		}
+	}
+
+	fn is_64(&self) -> bool {
+		self.pointer_width == PointerWidth::Bits64
+	}
+
+	fn writer(&self) -> Writer {
+		Writer {
+			bytes: Vec::new(),
+			is_64: self.is_64(),
+		}
+	}
+
+	/// Lays out the linkstore section, returning its contents and the containers within it.
+	fn section(&self) -> (Vec<u8>, Vec<Container>) {
+		let mut section = Vec::new();
+		let mut containers = Vec::new();
+		for entry in self.entries.iter() {
+			section.resize(section.len().next_multiple_of(entry.align), 0);
+
+			let offset = section.len();
+			section.extend_from_slice(&container(&entry.name, &entry.bytes, self.pointer_width, entry.align));
+
+			// Mimics the mangled name of the static generated by `linkstore!`
+			let ident = entry.name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+			containers.push(Container {
+				symbol: format!("_ZN8fixtures{}{ident}21__LINKSTORE_CONTAINER17h0000000000000000E", ident.len()),
+				offset: offset as u64,
+				size: (section.len() - offset) as u64,
+			});
+		}
+
+		if !self.symbols {
+			containers.clear();
+		}
+
+		(section, containers)
+	}
+
+	fn elf(&self, relocatable: bool) -> Vec<u8> {
+		const SHT_PROGBITS: u32 = 1;
+		const SHT_SYMTAB: u32 = 2;
+		const SHT_STRTAB: u32 = 3;
+		const SHF_ALLOC: u64 = 2;
+		const STT_OBJECT: u8 = 1;
+
+		let is_64 = self.is_64();
+		let (section, containers) = self.section();
+		let base = if relocatable { 0 } else { 0x400000 };
+
+		let mut elf = self.writer();
+		elf.bytes.resize(if is_64 { 64 } else { 52 }, 0);
+
+		elf.align(SECTION_ALIGN);
+		let section_offset = elf.len();
+		elf.bytes.extend_from_slice(&section);
+		let section_address = if relocatable { 0 } else { base + section_offset };
+
+		let mut strtab = vec![0];
+		let mut symtab = self.writer();
+		symtab.bytes.resize(if is_64 { 24 } else { 16 }, 0);
+		for container in containers.iter() {
+			let name = strtab.len() as u32;
+			strtab.extend_from_slice(container.symbol.as_bytes());
+			strtab.push(0);
+
+			let value = section_address + container.offset;
+			symtab.u32(name);
+			if is_64 {
+				symtab.u8(STT_OBJECT);
+				symtab.u8(0);
+				symtab.u16(1);
+				symtab.u64(value);
+				symtab.u64(container.size);
+			} else {
+				symtab.u32(value as u32);
+				symtab.u32(container.size as u32);
+				symtab.u8(STT_OBJECT);
+				symtab.u8(0);
+				symtab.u16(1);
+			}
+		}
+
+		elf.align(8);
+		let symtab_offset = elf.len();
+		elf.bytes.extend_from_slice(&symtab.bytes);
+		let strtab_offset = elf.len();
+		elf.bytes.extend_from_slice(&strtab);
+		let shstrtab_offset = elf.len();
+		let shstrtab = [b"\0", self.section_name.as_bytes(), b"\0.symtab\0.strtab\0.shstrtab\0"].concat();
+		let symtab_name = 2 + self.section_name.len() as u32;
+		elf.bytes.extend_from_slice(&shstrtab);
+
+		elf.align(8);
+		let section_headers = elf.len();
+		let mut section_header =
+			|name: u32, kind: u32, flags: u64, address: u64, offset: u64, size: u64, link: u32, info: u32, align: u64, entsize: u64| {
+				elf.u32(name);
+				elf.u32(kind);
+				elf.word(flags);
+				elf.word(address);
+				elf.word(offset);
+				elf.word(size);
+				elf.u32(link);
+				elf.u32(info);
+				elf.word(align);
+				elf.word(entsize);
+			};
+		section_header(0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+		section_header(
+			1,
+			SHT_PROGBITS,
+			SHF_ALLOC,
+			section_address,
+			section_offset,
+			section.len() as u64,
+			0,
+			0,
+			SECTION_ALIGN as u64,
+			0,
+		);
+		section_header(
+			symtab_name,
+			SHT_SYMTAB,
+			0,
+			0,
+			symtab_offset,
+			symtab.len(),
+			3,
+			containers.len() as u32 + 1,
+			8,
+			if is_64 { 24 } else { 16 },
+		);
+		section_header(symtab_name + 8, SHT_STRTAB, 0, 0, strtab_offset, strtab.len() as u64, 0, 0, 1, 0);
+		section_header(symtab_name + 16, SHT_STRTAB, 0, 0, shstrtab_offset, shstrtab.len() as u64, 0, 0, 1, 0);
+
+		let mut header = self.writer();
+		header.bytes.extend_from_slice(b"\x7fELF");
+		header.u8(if is_64 { 2 } else { 1 });
+		header.bytes.extend_from_slice(&[1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+		header.u16(if relocatable { 1 } else { 2 });
+		header.u16(if is_64 { 62 } else { 3 });
+		header.u32(1);
+		header.word(0);
+		header.word(0);
+		header.word(section_headers);
+		header.u32(0);
+		header.u16(if is_64 { 64 } else { 52 });
+		header.u16(if is_64 { 56 } else { 32 });
+		header.u16(0);
+		header.u16(if is_64 { 64 } else { 40 });
+		header.u16(5);
+		header.u16(4);
+		elf.bytes[..header.bytes.len()].copy_from_slice(&header.bytes);
+
+		elf.bytes
+	}
+
+	fn coff_machine(&self) -> u16 {
+		if self.is_64() {
+			0x8664
+		} else {
+			0x14C
+		}
+	}
+
+	/// Writes a COFF symbol table and string table for the linkstores in section 1, with the string table beginning with `strings`.
+	fn coff_symbols(containers: &[Container], mut strings: Vec<u8>, out: &mut Writer) {
+		for container in containers.iter() {
+			out.u32(0);
+			out.u32(4 + strings.len() as u32);
+			strings.extend_from_slice(container.symbol.as_bytes());
+			strings.push(0);
+
+			out.u32(container.offset as u32);
+			out.u16(1);
+			out.u16(0);
+			out.u8(3);
+			out.u8(0);
+		}
+		out.u32(4 + strings.len() as u32);
+		out.bytes.extend_from_slice(&strings);
+	}
+
+	fn coff_section_header(out: &mut Writer, name: &[u8], virtual_size: u32, virtual_address: u32, raw_size: u32, raw_offset: u32) {
+		let mut field = [0; 8];
+		field[..name.len().min(8)].copy_from_slice(&name[..name.len().min(8)]);
+		out.bytes.extend_from_slice(&field);
+		out.u32(virtual_size);
+		out.u32(virtual_address);
+		out.u32(raw_size);
+		out.u32(raw_offset);
+		out.u32(0);
+		out.u32(0);
+		out.u16(0);
+		out.u16(0);
+		out.u32(0x4050_0040);
+	}
+
+	fn coff(&self) -> Vec<u8> {
+		let (section, containers) = self.section();
+
+		let mut coff = self.writer();
+		coff.bytes.resize(20, 0);
+		// Longer names are stored in the string table, and the section refers to them by their offset
+		let (name, strings) = if self.section_name.len() > 8 {
+			(b"/4".to_vec(), [self.section_name.as_bytes(), b"\0"].concat())
+		} else {
+			(self.section_name.as_bytes().to_vec(), Vec::new())
+		};
+		Self::coff_section_header(&mut coff, &name, 0, 0, section.len() as u32, 64);
+		coff.align(SECTION_ALIGN);
+		coff.bytes.extend_from_slice(&section);
+
+		// goblin requires a string table, even if there are no symbols
+		let symbols = coff.len() as u32;
+		Self::coff_symbols(&containers, strings, &mut coff);
+
+		let mut header = self.writer();
+		header.u16(self.coff_machine());
+		header.u16(1);
+		header.u32(0);
+		header.u32(symbols);
+		header.u32(containers.len() as u32);
+		header.u16(0);
+		header.u16(0);
+		coff.bytes[..20].copy_from_slice(&header.bytes);
+
+		coff.bytes
+	}
+
+	fn pe(&self) -> Vec<u8> {
+		const FILE_ALIGN: usize = 0x200;
+		const SECTION_RVA: u32 = 0x1000;
+
+		let is_64 = self.is_64();
+		let (section, containers) = self.section();
+		let raw_size = section.len().next_multiple_of(FILE_ALIGN);
+		let symbols = FILE_ALIGN + raw_size;
+
+		let mut pe = self.writer();
+		pe.bytes.extend_from_slice(b"MZ");
+		pe.bytes.resize(0x3C, 0);
+		pe.u32(0x40);
+		pe.bytes.extend_from_slice(b"PE\0\0");
+
+		pe.u16(self.coff_machine());
+		pe.u16(1);
+		pe.u32(0);
+		pe.u32(if containers.is_empty() { 0 } else { symbols as u32 });
+		pe.u32(containers.len() as u32);
+		pe.u16(if is_64 { 240 } else { 224 });
+		pe.u16(if is_64 { 0x22 } else { 0x102 });
+
+		pe.u16(if is_64 { 0x20B } else { 0x10B });
+		pe.u8(14);
+		pe.u8(0);
+		pe.u32(0);
+		pe.u32(raw_size as u32);
+		pe.u32(0);
+		pe.u32(SECTION_RVA);
+		pe.u32(SECTION_RVA);
+		if !is_64 {
+			pe.u32(SECTION_RVA);
+		}
+		pe.word(if is_64 { 0x1_4000_0000 } else { 0x40_0000 });
+		pe.u32(0x1000);
+		pe.u32(FILE_ALIGN as u32);
+		for version in [6, 0, 0, 0, 6, 0] {
+			pe.u16(version);
+		}
+		pe.u32(0);
+		pe.u32(SECTION_RVA + (section.len().next_multiple_of(0x1000) as u32));
+		pe.u32(FILE_ALIGN as u32);
+		pe.u32(0);
+		pe.u16(3);
+		pe.u16(0);
+		for size in [0x10_0000, 0x1000, 0x10_0000, 0x1000] {
+			pe.word(size);
+		}
+		pe.u32(0);
+		pe.u32(16);
+		let data_directories = pe.bytes.len();
+		pe.bytes.resize(pe.bytes.len() + 16 * 8, 0);
+
+		Self::coff_section_header(
+			&mut pe,
+			self.section_name.as_bytes(),
+			section.len() as u32,
+			SECTION_RVA,
+			raw_size as u32,
+			FILE_ALIGN as u32,
+		);
+
+		pe.bytes.resize(FILE_ALIGN, 0);
+		pe.bytes.extend_from_slice(&section);
+		pe.bytes.resize(symbols, 0);
+
+		// MinGW images keep a COFF symbol table
+		if !containers.is_empty() {
+			Self::coff_symbols(&containers, Vec::new(), &mut pe);
+		}
+
+		// The certificate table is appended to the image, and its data directory entry holds a file offset rather than an address
+		if self.signed {
+			const WIN_CERT_REVISION_2_0: u16 = 0x200;
+			const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 2;
+
+			pe.align(8);
+			let certificate_table = pe.len() as u32;
+			pe.u32(16);
+			pe.u16(WIN_CERT_REVISION_2_0);
+			pe.u16(WIN_CERT_TYPE_PKCS_SIGNED_DATA);
+			pe.u64(0);
+
+			let entry = data_directories + 4 * 8;
+			pe.bytes[entry..entry + 4].copy_from_slice(&certificate_table.to_le_bytes());
+			pe.bytes[entry + 4..entry + 8].copy_from_slice(&16_u32.to_le_bytes());
+		}
+
+		pe.bytes
+	}
+
+	/// The CPU types of the slices in a fat Mach-O binary, or of a thin one.
+	fn mach_cputypes(&self) -> [(u32, u32); 2] {
+		if self.is_64() {
+			// x86_64, arm64
+			[(0x0100_0007, 3), (0x0100_000C, 0)]
+		} else {
+			// x86, armv7
+			[(7, 3), (12, 9)]
+		}
+	}
+
+	fn macho(&self, (cputype, cpusubtype): (u32, u32), object: bool) -> Vec<u8> {
+		const LC_SEGMENT: u32 = 0x1;
+		const LC_SEGMENT_64: u32 = 0x19;
+		const LC_SYMTAB: u32 = 0x2;
+		const LC_CODE_SIGNATURE: u32 = 0x1D;
+		const N_SECT: u8 = 0xE;
+
+		let is_64 = self.is_64();
+		let (section, containers) = self.section();
+		let base = match (object, is_64) {
+			(true, _) => 0,
+			(false, true) => 0x1_0000_0000,
+			(false, false) => 0x1000,
+		};
+
+		let segment_size = if is_64 { 72 + 80 } else { 56 + 68 };
+		let linkedit_size = if is_64 { 72 } else { 56 };
+		let signed = self.signed && !object;
+
+		let mut ncmds = 1;
+		let mut sizeofcmds = segment_size;
+		if !containers.is_empty() {
+			ncmds += 1;
+			sizeofcmds += 24;
+		}
+		if signed {
+			ncmds += 2;
+			sizeofcmds += linkedit_size + 16;
+		}
+
+		let mut macho = self.writer();
+		macho.u32(if is_64 { 0xFEED_FACF } else { 0xFEED_FACE });
+		macho.u32(cputype);
+		macho.u32(cpusubtype);
+		macho.u32(if object { 1 } else { 2 });
+		macho.u32(ncmds);
+		macho.u32(sizeofcmds as u32);
+		macho.u32(0);
+		if is_64 {
+			macho.u32(0);
+		}
+
+		let commands = macho.len() as usize;
+		let section_offset = (commands + sizeofcmds).next_multiple_of(SECTION_ALIGN) as u64;
+		let section_address = base + section_offset;
+
+		let (segname, sectname) = self.section_name.split_once(',').unwrap_or(("__TEXT", &self.section_name));
+
+		fn name(name: &[u8], out: &mut Writer) {
+			out.bytes.extend_from_slice(name);
+			out.bytes.resize(out.bytes.len() + 16 - name.len(), 0);
+		}
+
+		macho.u32(if is_64 { LC_SEGMENT_64 } else { LC_SEGMENT });
+		macho.u32(segment_size as u32);
+		name(segname.as_bytes(), &mut macho);
+		macho.word(base);
+		macho.word((section_offset + section.len() as u64).next_multiple_of(0x1000));
+		macho.word(0);
+		macho.word(section_offset + section.len() as u64);
+		macho.u32(5);
+		macho.u32(5);
+		macho.u32(1);
+		macho.u32(0);
+
+		name(sectname.as_bytes(), &mut macho);
+		name(segname.as_bytes(), &mut macho);
+		macho.word(section_address);
+		macho.word(section.len() as u64);
+		macho.u32(section_offset as u32);
+		macho.u32(SECTION_ALIGN.trailing_zeros());
+		for _ in 0..if is_64 { 6 } else { 5 } {
+			macho.u32(0);
+		}
+
+		let symtab = macho.len() as usize;
+		if !containers.is_empty() {
+			macho.bytes.resize(symtab + 24, 0);
+		}
+
+		// The code signature must be the last load command
+		let linkedit = macho.len() as usize;
+		if signed {
+			macho.bytes.resize(linkedit + linkedit_size + 16, 0);
+		}
+
+		macho.bytes.resize(section_offset as usize, 0);
+		macho.bytes.extend_from_slice(&section);
+
+		if !containers.is_empty() {
+			macho.align(8);
+			let symoff = macho.len();
+
+			let mut strings = vec![0];
+			for container in containers.iter() {
+				macho.u32(strings.len() as u32);
+				strings.push(b'_');
+				strings.extend_from_slice(container.symbol.as_bytes());
+				strings.push(0);
+
+				macho.u8(N_SECT);
+				macho.u8(1);
+				macho.u16(0);
+				macho.word(section_address + container.offset);
+			}
+
+			let stroff = macho.len();
+			macho.bytes.extend_from_slice(&strings);
+
+			let mut command = self.writer();
+			command.u32(LC_SYMTAB);
+			command.u32(24);
+			command.u32(symoff as u32);
+			command.u32(containers.len() as u32);
+			command.u32(stroff as u32);
+			command.u32(strings.len() as u32);
+			macho.bytes[symtab..symtab + 24].copy_from_slice(&command.bytes);
+		}
+
+		// The signature is at the end of the __LINKEDIT segment, which spans everything after the segment containing the linkstores
+		if signed {
+			const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xFADE_0CC0;
+
+			macho.align(16);
+			let dataoff = macho.len();
+			for field in [CSMAGIC_EMBEDDED_SIGNATURE, 16, 0, 0] {
+				macho.bytes.extend_from_slice(&field.to_be_bytes());
+			}
+
+			let fileoff = section_offset + section.len() as u64;
+			let mut command = self.writer();
+			command.u32(if is_64 { LC_SEGMENT_64 } else { LC_SEGMENT });
+			command.u32(linkedit_size as u32);
+			name(b"__LINKEDIT", &mut command);
+			command.word(base + fileoff.next_multiple_of(0x1000));
+			command.word((macho.len() - fileoff).next_multiple_of(0x1000));
+			command.word(fileoff);
+			command.word(macho.len() - fileoff);
+			command.u32(1);
+			command.u32(1);
+			command.u32(0);
+			command.u32(0);
+
+			command.u32(LC_CODE_SIGNATURE);
+			command.u32(16);
+			command.u32(dataoff as u32);
+			command.u32(16);
+			macho.bytes[linkedit..linkedit + linkedit_size + 16].copy_from_slice(&command.bytes);
+		}
+
+		macho.bytes
+	}
+
+	fn fat_macho(&self, object: bool) -> Vec<u8> {
+		const FAT_ALIGN: u32 = 12;
+
+		let slices = self.mach_cputypes().map(|cputype| (cputype, self.macho(cputype, object)));
+
+		let mut fat = Vec::new();
+		fat.extend_from_slice(&0xCAFE_BABE_u32.to_be_bytes());
+		fat.extend_from_slice(&(slices.len() as u32).to_be_bytes());
+
+		let mut offset = (8 + slices.len() * 20).next_multiple_of(1 << FAT_ALIGN);
+		for ((cputype, cpusubtype), slice) in slices.iter() {
+			for field in [*cputype, *cpusubtype, offset as u32, slice.len() as u32, FAT_ALIGN] {
+				fat.extend_from_slice(&field.to_be_bytes());
+			}
+			offset = (offset + slice.len()).next_multiple_of(1 << FAT_ALIGN);
+		}
+
+		for (_, slice) in slices.iter() {
+			fat.resize(fat.len().next_multiple_of(1 << FAT_ALIGN), 0);
+			fat.extend_from_slice(slice);
+		}
+
+		fat
+	}
+
+	fn wasm(&self) -> Vec<u8> {
+		const ADDRESS: i64 = 1024;
+
+		let is_64 = self.is_64();
+		let (section, _) = self.section();
+
+		let mut memory = vec![1, if is_64 { 0x04 } else { 0 }];
+		leb128(&mut memory, 1);
+
+		let mut data = vec![1, 0, if is_64 { 0x42 } else { 0x41 }];
+		sleb128(&mut data, ADDRESS);
+		data.push(0x0B);
+		leb128(&mut data, section.len() as u64);
+		data.extend_from_slice(&section);
+
+		let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+		for (id, contents) in [(5, memory), (11, data)] {
+			wasm.push(id);
+			leb128(&mut wasm, contents.len() as u64);
+			wasm.extend_from_slice(&contents);
+		}
+		wasm
+	}
+}
+
+/// Encodes a linkstore in the layout emitted by `linkstore!`, with its value aligned to `align` bytes from the start of the container.
+pub(crate) fn container(name: &str, value: &[u8], pointer_width: PointerWidth, align: usize) -> Vec<u8> {
+	let header = MAGIC.len() + name.len() + 1 + pointer_width.bytes() * 2 + 4;
+	let padding = (align - header % align) % align;
+
+	let mut container = encode_header(name, value.len() as u64, padding as u64, pointer_width.bytes());
+	container.resize(container.len() + padding, 0);
+	container.extend_from_slice(value);
+	container
+}
+
+/// Wraps an object file in an ar archive.
+fn archive(name: &str, member: &[u8]) -> Vec<u8> {
+	let mut archive = b"!<arch>\n".to_vec();
+	archive.extend_from_slice(format!("{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n", format!("{name}/"), 0, 0, 0, 644, member.len()).as_bytes());
+	archive.extend_from_slice(member);
+	if member.len() % 2 != 0 {
+		archive.push(b'\n');
+	}
+	archive
+}
+
+fn leb128(out: &mut Vec<u8>, mut value: u64) {
+	loop {
+		let byte = (value & 0x7F) as u8;
+		value >>= 7;
+		if value == 0 {
+			out.push(byte);
+			return;
+		}
+		out.push(byte | 0x80);
+	}
+}
+
+fn sleb128(out: &mut Vec<u8>, mut value: i64) {
+	loop {
+		let byte = (value & 0x7F) as u8;
+		value >>= 7;
+		if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+			out.push(byte);
+			return;
+		}
+		out.push(byte | 0x80);
+	}
+}
+
+/// Writes little-endian fields, with words the size of the target's pointers.
+struct Writer {
+	bytes: Vec<u8>,
+	is_64: bool,
+}
+impl Writer {
+	fn len(&self) -> u64 {
+		self.bytes.len() as u64
+	}
+
+	fn align(&mut self, align: usize) {
+		self.bytes.resize(self.bytes.len().next_multiple_of(align), 0);
+	}
+
+	fn u8(&mut self, value: u8) {
+		self.bytes.push(value);
+	}
+
+	fn u16(&mut self, value: u16) {
+		self.bytes.extend_from_slice(&value.to_le_bytes());
+	}
+
+	fn u32(&mut self, value: u32) {
+		self.bytes.extend_from_slice(&value.to_le_bytes());
+	}
+
+	fn u64(&mut self, value: u64) {
+		self.bytes.extend_from_slice(&value.to_le_bytes());
+	}
+
+	fn word(&mut self, value: u64) {
+		if self.is_64 {
+			self.u64(value);
+		} else {
+			self.u32(value as u32);
+		}
+	}
+}
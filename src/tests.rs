@@ -29,7 +29,7 @@ impl LinkStoreTest {
 
 fn build(target: &str) {
 	assert!(Command::new("cargo")
-		.args(&[
+		.args([
 			"clean",
 			"--manifest-path",
 			"tests/Cargo.toml",
@@ -43,7 +43,7 @@ fn build(target: &str) {
 		.success());
 
 	assert!(Command::new("cargo")
-		.args(&[
+		.args([
 			"build",
 			"--profile",
 			"linkstore-test-release",
@@ -72,6 +72,11 @@ unsafe fn first_pass<'a, IO: BinaryHandle<'a>>(embedder: &mut Embedder<'a, IO>)
 		Some(Ok([0xDE, 0xAD, 0xBE, 0xEF]))
 	));
 	assert_eq!(embedder.read::<u128>("LINKSTORE_BIG").unwrap().next(), Some(0xDEADBEEF_u128));
+	assert_eq!(
+		embedder.read::<u64>("linkstore_tests::LINKSTORE_TEST").unwrap().next(),
+		Some(0xCAFEBABE_u64)
+	);
+	assert_eq!(embedder.read::<u16>("linkstore_tests::app.net.port").unwrap().next(), Some(8080_u16));
 	embedder.embed("LINKSTORE_TEST", &69_u64).unwrap();
 	embedder.embed("LINKSTORE_YEAH", &420_u32).unwrap();
 	embedder.embed("LINKSTORE_BYTES", &[1_u8, 2, 3, 4]).unwrap();
@@ -93,9 +98,86 @@ unsafe fn second_pass<'a, IO: BinaryHandle<'a>>(embedder: &mut Embedder<'a, IO>)
 	assert_eq!(embedder.read::<u128>("LINKSTORE_BIG").unwrap().next(), Some(u128::MAX / 2));
 }
 
+fn test_manifest<'a, IO: BinaryHandle<'a>>(embedder: &Embedder<'a, IO>) -> Manifest {
+	let mut manifest = embedder.export();
+	assert_eq!(
+		manifest.entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(),
+		[
+			"LINKSTORE_BIG",
+			"LINKSTORE_BYTES",
+			"LINKSTORE_SHORTS",
+			"LINKSTORE_TEST",
+			"LINKSTORE_YEAH",
+			"linkstore_tests::LINKSTORE_TEST",
+			"linkstore_tests::app.net.port"
+		]
+	);
+	assert_eq!(
+		embedder.namespaces(),
+		[
+			(
+				None,
+				vec!["LINKSTORE_BIG", "LINKSTORE_BYTES", "LINKSTORE_SHORTS", "LINKSTORE_TEST", "LINKSTORE_YEAH"]
+			),
+			(
+				Some("linkstore_tests"),
+				vec!["linkstore_tests::LINKSTORE_TEST", "linkstore_tests::app.net.port"]
+			)
+		]
+		.into_iter()
+		.collect()
+	);
+
+	manifest
+		.interpret("LINKSTORE_TEST", TypeHint::U64)
+		.unwrap()
+		.interpret("LINKSTORE_SHORTS", TypeHint::Array(Box::new(TypeHint::U16), 4))
+		.unwrap();
+	assert!(matches!(
+		manifest.interpret("LINKSTORE_YEAH", TypeHint::U64),
+		Err(Error::MismatchedSize(4, 8))
+	));
+
+	let test = manifest.get("LINKSTORE_TEST").unwrap();
+	assert_eq!(test.size, 8);
+	assert!(test.occurrences.iter().all(|occurrence| occurrence.bytes == 69_u64.to_le_bytes()));
+	assert!(test.occurrences.iter().all(|occurrence| occurrence.value == Some(ManifestValue::U64(69))));
+
+	let shorts = manifest.get("LINKSTORE_SHORTS").unwrap();
+	assert!(shorts.occurrences.iter().all(|occurrence| occurrence.value
+		== Some(ManifestValue::Array(vec![
+			ManifestValue::U16(1),
+			ManifestValue::U16(2),
+			ManifestValue::U16(3),
+			ManifestValue::U16(4)
+		]))));
+
+	manifest
+}
+
+fn test_locations<'a, IO: BinaryHandle<'a>>(embedder: &Embedder<'a, IO>) {
+	for name in embedder.keys() {
+		for location in embedder.locations(name).unwrap() {
+			assert_eq!(location.section, ".lnkstre");
+			assert_ne!(location.arch, Architecture::Unknown);
+			assert_eq!(location.member.is_some(), location.virtual_address.is_none());
+			assert_eq!(location.segment.is_some(), location.format == Format::MachO);
+		}
+	}
+}
+
 fn test_executable(path: &str, lib: bool, open: bool) {
 	println!("Testing {path:?} (library: {lib})");
 
+	#[cfg(unix)]
+	{
+		// Before stripping, linkstores can be located by their symbols in any section
+		let mut binary = crate::open_binary(path).unwrap();
+		let mut embedder = Embedder::builder().discovery(DiscoveryMode::Symbols).build(&mut binary).unwrap();
+		assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_YEAH") }.unwrap(), 0xDEADBEEF);
+		assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_SECTIONED") }.unwrap(), 0xFEEDFACE);
+	}
+
 	#[cfg(unix)]
 	{
 		#[cfg(not(target_os = "macos"))]
@@ -114,6 +196,9 @@ fn test_executable(path: &str, lib: bool, open: bool) {
 		}
 	}
 
+	let unstamped = format!("{path}.unstamped");
+	std::fs::copy(path, &unstamped).unwrap();
+
 	{
 		let mut binary = crate::open_binary(path).unwrap();
 		let mut embedder = Embedder::new(&mut binary).unwrap();
@@ -121,17 +206,96 @@ fn test_executable(path: &str, lib: bool, open: bool) {
 		embedder.finish().unwrap();
 	}
 
+	let manifest = {
+		let mut binary = crate::open_binary(path).unwrap();
+		let mut embedder = Embedder::builder().strict(true).build(&mut binary).unwrap();
+		unsafe { second_pass(&mut embedder) };
+		assert!(embedder.duplicates().iter().all(|duplicate| duplicate.agree()));
+		assert_eq!(unsafe { embedder.read_unique::<u64>("LINKSTORE_TEST") }.unwrap(), 69);
+		assert!(matches!(
+			unsafe { embedder.read_unique::<u32>("LINKSTORE_TEST") },
+			Err(Error::MismatchedSize(8, 4))
+		));
+		test_locations(&embedder);
+		test_manifest(&embedder)
+	};
+
+	{
+		let mut binary = crate::open_binary(path).unwrap();
+		let mut embedder = Embedder::new(&mut binary).unwrap();
+
+		embedder
+			.embed_where("LINKSTORE_YEAH", &0_u32, |location| location.arch == Architecture::Unknown)
+			.unwrap();
+		assert!(unsafe { embedder.read::<u32>("LINKSTORE_YEAH") }.unwrap().all(|value| value == 420));
+
+		let first = manifest.get("LINKSTORE_YEAH").unwrap().occurrences[0].offset;
+		embedder
+			.embed_where("LINKSTORE_YEAH", &1337_u32, |location| location.offset == first)
+			.unwrap();
+		let mut values = unsafe { embedder.read::<u32>("LINKSTORE_YEAH") }.unwrap();
+		assert_eq!(values.next(), Some(1337));
+		assert!(values.all(|value| value == 420));
+	}
+
 	{
 		let mut binary = crate::open_binary(path).unwrap();
+		let mut embedder = Embedder::builder()
+			.section_name(".lnkstre")
+			.section_name(".lnkcfg")
+			.build(&mut binary)
+			.unwrap();
+		assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_SECTIONED") }.unwrap(), 0xFEEDFACE);
+		assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_YEAH") }.unwrap(), 420);
+		assert!(embedder
+			.locations("LINKSTORE_SECTIONED")
+			.unwrap()
+			.all(|location| location.section == ".lnkcfg"));
+
+		let mut binary = crate::open_binary(path).unwrap();
+		let embedder = Embedder::new(&mut binary).unwrap();
+		assert!(matches!(embedder.locations("LINKSTORE_SECTIONED"), Err(Error::NotPresent(_))));
+	}
+
+	{
+		let mut binary = crate::open_binary(&unstamped).unwrap();
+		let mut embedder = Embedder::new(&mut binary).unwrap();
+		embedder.import(&manifest).unwrap();
+		embedder.finish().unwrap();
+	}
+
+	{
+		let mut binary = crate::open_binary(&unstamped).unwrap();
 		let mut embedder = Embedder::new(&mut binary).unwrap();
 		unsafe { second_pass(&mut embedder) };
+		assert_eq!(
+			embedder
+				.export()
+				.entries
+				.into_iter()
+				.map(|entry| entry.occurrences.len())
+				.collect::<Vec<_>>(),
+			manifest.entries.iter().map(|entry| entry.occurrences.len()).collect::<Vec<_>>()
+		);
+	}
+
+	{
+		let mut binary = crate::open_binary(path).unwrap();
+		let mut embedder = Embedder::new(&mut binary).unwrap();
+		let inserted = 1337_u32.to_le_bytes();
+		embedder.insert("LINKSTORE_INSERTED", &inserted).unwrap();
+		embedder.finish().unwrap();
+
+		let mut binary = crate::open_binary(path).unwrap();
+		let mut embedder = Embedder::builder().strict(true).build(&mut binary).unwrap();
+		assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_INSERTED") }.unwrap(), 1337);
 	}
 
 	#[cfg(target_os = "macos")]
 	{
 		// We need to resign the binary to be able to run it
 		assert!(Command::new("codesign")
-			.args(&["--force", "--sign", "-", path])
+			.args(["--force", "--sign", "-", path])
 			.status()
 			.unwrap()
 			.success());
@@ -174,6 +338,1773 @@ fn test_executable(path: &str, lib: bool, open: bool) {
 	}
 }
 
+fn wasm_section(id: u8, contents: &[u8]) -> Vec<u8> {
+	assert!(contents.len() < 0x80);
+	[&[id, contents.len() as u8], contents].concat()
+}
+
+#[test]
+fn wasm() {
+	// A corrupted header must be skipped without losing the linkstore after it
+	let mut corrupted = testing::container("LINKSTORE_CORRUPTED", &0xDEADBEEF_u32.to_le_bytes(), PointerWidth::Bits32, 4);
+	corrupted[embed::encode::MAGIC.len()] ^= 0xFF;
+
+	let data = [
+		&[0xAA; 12][..],
+		&corrupted,
+		&testing::container("LINKSTORE_WASM", &0xDEADBEEF_u32.to_le_bytes(), PointerWidth::Bits32, 4),
+	]
+	.concat();
+	let custom = [
+		&b"\x08.lnkstre"[..],
+		&testing::container("LINKSTORE_CUSTOM", &0xBEEF_u16.to_le_bytes(), PointerWidth::Bits32, 2),
+	]
+	.concat();
+
+	let mut module = [
+		&b"\0asm\x01\0\0\0"[..],
+		// 1 memory, no maximum, 1 page
+		&wasm_section(5, &[1, 0, 1]),
+		// 1 active segment at i32.const 1024
+		&wasm_section(11, &[&[1, 0, 0x41, 0x80, 0x08, 0x0B, data.len() as u8][..], &data].concat()),
+		&wasm_section(0, &custom),
+	]
+	.concat();
+
+	{
+		let mut module = std::io::Cursor::new(&mut module[..]);
+		let mut embedder = Embedder::new(&mut module).unwrap();
+
+		assert!(matches!(embedder.locations("LINKSTORE_CORRUPTED"), Err(Error::NotPresent(_))));
+
+		let location = embedder.locations("LINKSTORE_WASM").unwrap().next().unwrap();
+		assert_eq!(location.format, Format::Wasm);
+		assert_eq!(location.arch, Architecture::Wasm32);
+		assert_eq!(location.pointer_width, PointerWidth::Bits32);
+		assert_eq!(location.section, "data[0]");
+		assert_eq!(location.virtual_address, Some(1024 + data.len() as u64 - 4));
+
+		let location = embedder.locations("LINKSTORE_CUSTOM").unwrap().next().unwrap();
+		assert_eq!(location.section, ".lnkstre");
+		assert_eq!(location.virtual_address, None);
+
+		unsafe {
+			assert_eq!(embedder.read_unique::<u32>("LINKSTORE_WASM").unwrap(), 0xDEADBEEF);
+			assert_eq!(embedder.read_unique::<u16>("LINKSTORE_CUSTOM").unwrap(), 0xBEEF);
+		}
+
+		embedder.embed("LINKSTORE_WASM", &69_u32).unwrap();
+		embedder.embed("LINKSTORE_CUSTOM", &420_u16).unwrap();
+		embedder.finish().unwrap();
+	}
+
+	let mut module = std::io::Cursor::new(&mut module[..]);
+	let mut embedder = Embedder::new(&mut module).unwrap();
+	unsafe {
+		assert_eq!(embedder.read_unique::<u32>("LINKSTORE_WASM").unwrap(), 69);
+		assert_eq!(embedder.read_unique::<u16>("LINKSTORE_CUSTOM").unwrap(), 420);
+	}
+}
+
+#[test]
+fn malformed() {
+	let ar_header = |name: &str, size: usize| format!("{name:<16}{:<12}{:<6}{:<6}{:<8}{size:<10}`\n", 0, 0, 0, 644).into_bytes();
+
+	let binaries = [
+		// A custom section whose name overruns the section
+		[&b"\0asm\x01\0\0\0"[..], &wasm_section(0, &[0x05]), b"abcde"].concat(),
+		// An archive with an empty GNU name index
+		[&b"!<arch>\n"[..], &ar_header("//", 0)].concat(),
+	];
+	for mut binary in binaries {
+		assert!(Embedder::new(&mut std::io::Cursor::new(&mut binary[..])).is_err());
+	}
+
+	// Scanning only searches so far for the nul byte that ends a name, so longer names aren't linkstores
+	let long = "a".repeat(embed::encode::MAX_NAME_LEN + 1);
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry(&long, &0_u32)
+		.entry("LINKSTORE_YEAH", &0_u32)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let embedder = Embedder::new(&mut cursor).unwrap();
+	assert_eq!(embedder.keys().collect::<Vec<_>>(), ["LINKSTORE_YEAH"]);
+}
+
+/// Generates a fixture containing the linkstores in the tests crate, with the given values.
+fn fixture(format: Format, pointer_width: PointerWidth, test: u64, yeah: u32, shorts: [u16; 4], big: u128) -> testing::SyntheticBinary {
+	testing::SyntheticBinary::new(format, pointer_width)
+		.entry("LINKSTORE_TEST", &test)
+		.entry("LINKSTORE_YEAH", &yeah)
+		.entry("LINKSTORE_SHORTS", &shorts)
+		.entry("LINKSTORE_BIG", &big)
+		.entry("linkstore_tests::app.net.port", &8080_u16)
+}
+
+#[test]
+fn fixtures() {
+	use PointerWidth::*;
+
+	#[rustfmt::skip]
+	let fixtures = [
+		(Format::Elf, Bits64, false, false),
+		(Format::Elf, Bits32, false, false),
+		(Format::Elf, Bits64, true, false),
+		(Format::Pe, Bits64, false, false),
+		(Format::Pe, Bits32, false, false),
+		(Format::Coff, Bits64, true, false),
+		(Format::Coff, Bits32, true, false),
+		(Format::MachO, Bits64, false, false),
+		(Format::MachO, Bits32, false, false),
+		(Format::MachO, Bits64, false, true),
+		(Format::MachO, Bits64, true, false),
+		(Format::Wasm, Bits32, false, false),
+		(Format::Wasm, Bits64, false, false),
+	];
+
+	for (format, pointer_width, archive, fat) in fixtures {
+		let unstamped = |symbols| {
+			fixture(format, pointer_width, 0xDEADBEEF, 0xDEADBEEF, [0xDE, 0xAD, 0xBE, 0xEF], 0xDEADBEEF)
+				.symbols(symbols)
+				.archive(archive)
+				.fat(fat)
+				.build()
+		};
+		let stamped = |symbols| {
+			fixture(format, pointer_width, 69, 420, [1, 2, 3, 4], u128::MAX / 2)
+				.symbols(symbols)
+				.archive(archive)
+				.fat(fat)
+				.build()
+		};
+
+		for symbols in [false, true] {
+			let context = format!("{format:?} {pointer_width:?} (archive: {archive}, fat: {fat}, symbols: {symbols})");
+
+			let mut binary = unstamped(symbols);
+			{
+				let discovery = if symbols && format != Format::Wasm {
+					DiscoveryMode::Symbols
+				} else {
+					DiscoveryMode::Scan
+				};
+
+				let mut cursor = std::io::Cursor::new(&mut binary[..]);
+				let mut embedder = Embedder::builder().discovery(discovery).build(&mut cursor).unwrap();
+
+				let occurrences = if fat { 2 } else { 1 };
+				for location in embedder.locations("LINKSTORE_TEST").unwrap() {
+					assert_eq!(location.format, format, "{context}");
+					assert_eq!(location.pointer_width, pointer_width, "{context}");
+					assert_eq!(location.section, if format == Format::Wasm { "data[0]" } else { ".lnkstre" }, "{context}");
+					assert_eq!(location.member.is_some(), archive, "{context}");
+					assert_eq!(location.virtual_address.is_none(), archive, "{context}");
+				}
+				assert_eq!(embedder.locations("LINKSTORE_TEST").unwrap().count(), occurrences, "{context}");
+
+				unsafe {
+					assert!(
+						embedder.read::<u64>("LINKSTORE_TEST").unwrap().all(|value| value == 0xDEADBEEF),
+						"{context}"
+					);
+					assert!(
+						embedder.read::<u32>("LINKSTORE_YEAH").unwrap().all(|value| value == 0xDEADBEEF),
+						"{context}"
+					);
+					assert_eq!(embedder.read_unique::<u16>("linkstore_tests::app.net.port").unwrap(), 8080, "{context}");
+				}
+
+				embedder
+					.embed("LINKSTORE_TEST", &69_u64)
+					.unwrap()
+					.embed("LINKSTORE_YEAH", &420_u32)
+					.unwrap()
+					.embed("LINKSTORE_SHORTS", &[1_u16, 2, 3, 4])
+					.unwrap()
+					.embed("LINKSTORE_BIG", &(u128::MAX / 2))
+					.unwrap();
+				embedder.finish().unwrap();
+			}
+
+			// Embedding must only change the values, leaving the binary identical to one built with the new values
+			assert!(binary == stamped(symbols), "{context}");
+		}
+	}
+}
+
+/// Returns the file offsets of the checksums in the section definitions of every COFF object in an archive.
+///
+/// The compiler records a checksum of each section's contents, which the linker only checks for COMDAT sections, so the embedder leaves it alone.
+fn coff_section_checksums(bytes: &[u8]) -> Vec<usize> {
+	const SECTION_DEFINITION_CHECKSUM: usize = 8;
+
+	let archive = goblin::archive::Archive::parse(bytes).unwrap();
+	let mut checksums = Vec::new();
+	for member in archive.members() {
+		let member = archive.get(member).unwrap();
+		let start = member.offset as usize;
+		let coff = goblin::pe::Coff::parse(&bytes[start..][..member.size()]).unwrap();
+		for (index, _, symbol) in coff.symbols.iter() {
+			if symbol.storage_class == goblin::pe::symbol::IMAGE_SYM_CLASS_STATIC && symbol.value == 0 && symbol.number_of_aux_symbols > 0 {
+				let aux = coff.header.pointer_to_symbol_table as usize + (index + 1) * goblin::pe::symbol::COFF_SYMBOL_SIZE;
+				checksums.push(start + aux + SECTION_DEFINITION_CHECKSUM);
+			}
+		}
+	}
+	checksums
+}
+
+#[test]
+fn compiled_fixtures() {
+	// Built from `tests/fixtures/src/lib.rs` by `tests/fixtures/build.sh`, along with the same binaries compiled with the stamped values
+	let fixtures: [(&str, Format, &[u8], &[u8]); 5] = [
+		(
+			"elf-x86_64.so",
+			Format::Elf,
+			include_bytes!("../tests/fixtures/bin/elf-x86_64.so"),
+			include_bytes!("../tests/fixtures/bin/elf-x86_64.stamped.so"),
+		),
+		(
+			"pe-x86_64.dll",
+			Format::Pe,
+			include_bytes!("../tests/fixtures/bin/pe-x86_64.dll"),
+			include_bytes!("../tests/fixtures/bin/pe-x86_64.stamped.dll"),
+		),
+		(
+			"coff-x86_64.lib",
+			Format::Coff,
+			include_bytes!("../tests/fixtures/bin/coff-x86_64.lib"),
+			include_bytes!("../tests/fixtures/bin/coff-x86_64.stamped.lib"),
+		),
+		(
+			"macho-x86_64.dylib",
+			Format::MachO,
+			include_bytes!("../tests/fixtures/bin/macho-x86_64.dylib"),
+			include_bytes!("../tests/fixtures/bin/macho-x86_64.stamped.dylib"),
+		),
+		(
+			"wasm32.wasm",
+			Format::Wasm,
+			include_bytes!("../tests/fixtures/bin/wasm32.wasm"),
+			include_bytes!("../tests/fixtures/bin/wasm32.stamped.wasm"),
+		),
+	];
+
+	for (name, format, unstamped, stamped) in fixtures {
+		// DLLs and WebAssembly modules have no symbol table to locate linkstores with
+		let discoveries: &[DiscoveryMode] = match format {
+			Format::Pe | Format::Wasm => &[DiscoveryMode::Auto, DiscoveryMode::Scan],
+			_ => &[DiscoveryMode::Auto, DiscoveryMode::Symbols, DiscoveryMode::Scan],
+		};
+
+		for discovery in discoveries.iter().copied() {
+			let context = format!("{name} ({discovery:?})");
+
+			let mut binary = unstamped.to_vec();
+			{
+				let mut cursor = std::io::Cursor::new(&mut binary[..]);
+				let mut embedder = Embedder::builder().discovery(discovery).build(&mut cursor).unwrap();
+
+				for location in embedder.locations("LINKSTORE_TEST").unwrap() {
+					assert_eq!(location.format, format, "{context}");
+					assert_eq!(
+						location.pointer_width,
+						if format == Format::Wasm {
+							PointerWidth::Bits32
+						} else {
+							PointerWidth::Bits64
+						},
+						"{context}"
+					);
+				}
+
+				unsafe {
+					assert_eq!(embedder.read_unique::<u64>("LINKSTORE_TEST").unwrap(), 0xDEADBEEF, "{context}");
+					assert_eq!(embedder.read_unique::<u32>("LINKSTORE_YEAH").unwrap(), 0xDEADBEEF, "{context}");
+					assert_eq!(
+						embedder
+							.try_read::<[u16; 4]>("LINKSTORE_SHORTS")
+							.unwrap()
+							.collect::<Result<Vec<_>, _>>()
+							.unwrap(),
+						[[0xDE, 0xAD, 0xBE, 0xEF]],
+						"{context}"
+					);
+					assert_eq!(embedder.read_unique::<u128>("LINKSTORE_BIG").unwrap(), 0xDEADBEEF, "{context}");
+					assert_eq!(embedder.read_unique::<u16>("linkstore_tests::app.net.port").unwrap(), 8080, "{context}");
+				}
+
+				embedder
+					.embed("LINKSTORE_TEST", &69_u64)
+					.unwrap()
+					.embed("LINKSTORE_YEAH", &420_u32)
+					.unwrap()
+					.embed("LINKSTORE_SHORTS", &[1_u16, 2, 3, 4])
+					.unwrap()
+					.embed("LINKSTORE_BIG", &(u128::MAX / 2))
+					.unwrap();
+				embedder.finish().unwrap();
+			}
+
+			if format == Format::Coff {
+				for checksum in coff_section_checksums(&binary) {
+					binary[checksum..checksum + 4].copy_from_slice(&stamped[checksum..checksum + 4]);
+				}
+			}
+
+			// Embedding must leave the binary identical to the one the compiler and linker produced with the new values
+			assert!(binary == stamped, "{context}");
+		}
+	}
+}
+
+#[test]
+fn discovery() {
+	// Overwrites the first occurrence of `from` in the binary with `to`, which must be the same length
+	let patch = |binary: &mut Vec<u8>, from: &[u8], to: &[u8]| {
+		let offset = binary.windows(from.len()).position(|window| window == from).unwrap();
+		binary[offset..offset + to.len()].copy_from_slice(to);
+	};
+	let discover = |binary: &mut Vec<u8>, discovery: DiscoveryMode| {
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::builder().discovery(discovery).build(&mut cursor).unwrap();
+		["LINKSTORE_TEST", "LINKSTORE_YEAH"].map(|name| embedder.locations(name).map_or(0, Iterator::count))
+	};
+
+	for format in [Format::Elf, Format::Pe, Format::Coff, Format::MachO] {
+		let binary = fixture(format, PointerWidth::Bits64, 0xDEADBEEF, 0xDEADBEEF, [0xDE, 0xAD, 0xBE, 0xEF], 0xDEADBEEF)
+			.symbols(true)
+			.build();
+
+		// Linkstores found by both their symbols and scanning are only recorded once
+		for discovery in [DiscoveryMode::Auto, DiscoveryMode::Symbols, DiscoveryMode::Scan] {
+			assert_eq!(discover(&mut binary.clone(), discovery), [1, 1], "{format:?} {discovery:?}");
+		}
+
+		// A linkstore whose symbol was stripped, as `strip -x` does to local symbols, is still found by scanning
+		let mut stripped = binary.clone();
+		patch(
+			&mut stripped,
+			b"LINKSTORE_YEAH21__LINKSTORE_CONTAINER",
+			b"LINKSTORE_YEAH21__LINKSTORE_CONTAINEX",
+		);
+		assert_eq!(discover(&mut stripped.clone(), DiscoveryMode::Symbols), [1, 0], "{format:?}");
+		assert_eq!(discover(&mut stripped, DiscoveryMode::Auto), [1, 1], "{format:?}");
+
+		// A symbol pointing at bytes which aren't a valid linkstore is skipped, rather than failing discovery
+		let mut corrupt = binary.clone();
+		patch(
+			&mut corrupt,
+			&[&embed::encode::MAGIC[..], b"LINKSTORE_YEAH\0"].concat(),
+			&[&embed::encode::MAGIC[..], b"LINKSTORE_YEAX\0"].concat(),
+		);
+		for discovery in [DiscoveryMode::Auto, DiscoveryMode::Symbols] {
+			assert_eq!(discover(&mut corrupt.clone(), discovery), [1, 0], "{format:?} {discovery:?}");
+		}
+	}
+}
+
+#[test]
+fn manifest() {
+	let unstamped = || {
+		fixture(
+			Format::MachO,
+			PointerWidth::Bits64,
+			0xDEADBEEF,
+			0xDEADBEEF,
+			[0xDE, 0xAD, 0xBE, 0xEF],
+			0xDEADBEEF,
+		)
+		.fat(true)
+	};
+	let stamped = fixture(Format::MachO, PointerWidth::Bits64, 69, 420, [1, 2, 3, 4], u128::MAX / 2)
+		.fat(true)
+		.build();
+
+	let mut manifest = {
+		let mut binary = stamped.clone();
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		embedder.export()
+	};
+	assert_eq!(
+		manifest.entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(),
+		[
+			"LINKSTORE_BIG",
+			"LINKSTORE_SHORTS",
+			"LINKSTORE_TEST",
+			"LINKSTORE_YEAH",
+			"linkstore_tests::app.net.port"
+		]
+	);
+
+	manifest
+		.interpret("LINKSTORE_TEST", TypeHint::U64)
+		.unwrap()
+		.interpret("LINKSTORE_SHORTS", TypeHint::Array(Box::new(TypeHint::U16), 4))
+		.unwrap();
+	assert!(matches!(
+		manifest.interpret("LINKSTORE_YEAH", TypeHint::U64),
+		Err(Error::MismatchedSize(4, 8))
+	));
+	assert!(matches!(
+		manifest.interpret("LINKSTORE_MISSING", TypeHint::U64),
+		Err(Error::NotPresent(_))
+	));
+
+	let test = manifest.get("LINKSTORE_TEST").unwrap();
+	assert_eq!(test.size, 8);
+	assert_eq!(test.occurrences.len(), 2);
+	assert!(test.occurrences.iter().all(|occurrence| occurrence.value == Some(ManifestValue::U64(69))));
+	assert!(manifest
+		.get("LINKSTORE_SHORTS")
+		.unwrap()
+		.occurrences
+		.iter()
+		.all(|occurrence| occurrence.value == Some(ManifestValue::Array([1, 2, 3, 4].map(ManifestValue::U16).to_vec()))));
+
+	// Importing a manifest exported from a stamped binary stamps another binary identically
+	let mut binary = unstamped().build();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		embedder.import(&manifest).unwrap();
+		embedder.finish().unwrap();
+	}
+	assert!(binary == stamped);
+
+	// A deserialized manifest whose bytes don't agree with its size isn't interpreted
+	let mut edited = manifest.clone();
+	edited
+		.entries
+		.iter_mut()
+		.find(|entry| entry.name == "LINKSTORE_YEAH")
+		.unwrap()
+		.occurrences[1]
+		.bytes
+		.push(0);
+	assert!(matches!(
+		edited.interpret("LINKSTORE_YEAH", TypeHint::U32),
+		Err(Error::MismatchedSize(5, 4))
+	));
+	assert!(edited
+		.get("LINKSTORE_YEAH")
+		.unwrap()
+		.occurrences
+		.iter()
+		.all(|occurrence| occurrence.value.is_none()));
+
+	// Nothing is embedded if any entry of the manifest fails, even if the entries before it succeed
+	let mut missing = manifest.clone();
+	missing.entries.push(ManifestEntry {
+		name: "LINKSTORE_MISSING".to_string(),
+		size: 4,
+		occurrences: vec![ManifestOccurrence {
+			offset: 0,
+			bytes: vec![0; 4],
+			value: None,
+		}],
+	});
+	let mut diverging = manifest.clone();
+	diverging
+		.entries
+		.iter_mut()
+		.find(|entry| entry.name == "LINKSTORE_YEAH")
+		.unwrap()
+		.occurrences[1]
+		.bytes = vec![0; 4];
+	let mut three = diverging.clone();
+	let occurrence = three.entries.iter().find(|entry| entry.name == "LINKSTORE_YEAH").unwrap().occurrences[0].clone();
+	three
+		.entries
+		.iter_mut()
+		.find(|entry| entry.name == "LINKSTORE_YEAH")
+		.unwrap()
+		.occurrences
+		.push(occurrence);
+
+	let import = |manifest: &Manifest| {
+		let mut binary = unstamped().build();
+		let error = {
+			let mut cursor = std::io::Cursor::new(&mut binary[..]);
+			let mut embedder = Embedder::new(&mut cursor).unwrap();
+			let before = embedder.export();
+			let error = embedder.import(manifest).map(drop).unwrap_err();
+			assert_eq!(embedder.export(), before, "{error:?}");
+			embedder.finish().unwrap();
+			error
+		};
+		assert!(binary == unstamped().build(), "{error:?}");
+		error
+	};
+	assert!(matches!(import(&missing), Error::NotPresent(_)));
+	assert!(matches!(import(&edited), Error::MismatchedSize(4, 5)));
+	assert!(matches!(import(&three), Error::MismatchedOccurrences(_, 2, 3)));
+
+	// Diverging values are embedded into each occurrence in order
+	let mut binary = unstamped().build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	embedder.import(&diverging).unwrap();
+	assert_eq!(unsafe { embedder.read::<u32>("LINKSTORE_YEAH") }.unwrap().collect::<Vec<_>>(), [420, 0]);
+}
+
+#[test]
+fn namespaces() {
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry("LINKSTORE_YEAH", &0_u32)
+		.entry("LINKSTORE_TEST", &0_u64)
+		.entry("server::port", &0_u16)
+		.entry("server::app.net.host", &[0_u8; 4])
+		.entry("server::tls::enabled", &false)
+		.entry("client::retries", &0_u32)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let embedder = Embedder::new(&mut cursor).unwrap();
+
+	// Keys are grouped by everything before their last `::`, so nested namespaces are groups of their own
+	assert_eq!(
+		embedder.namespaces(),
+		[
+			(None, vec!["LINKSTORE_TEST", "LINKSTORE_YEAH"]),
+			(Some("client"), vec!["client::retries"]),
+			(Some("server"), vec!["server::app.net.host", "server::port"]),
+			(Some("server::tls"), vec!["server::tls::enabled"]),
+		]
+		.into_iter()
+		.collect()
+	);
+}
+
+#[test]
+fn duplicates() {
+	let mut binary = fixture(Format::MachO, PointerWidth::Bits64, 69, 420, [1, 2, 3, 4], u128::MAX / 2)
+		.fat(true)
+		.build();
+
+	// Gives the second slice a different value, as a universal binary stamped per architecture would have
+	let offset = {
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		let locations = embedder.locations("LINKSTORE_YEAH").unwrap().collect::<Vec<_>>();
+		assert_eq!(locations.len(), 2);
+		locations[1].offset as usize
+	};
+	binary[offset..offset + 4].copy_from_slice(&1337_u32.to_le_bytes());
+
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+
+		let duplicates = embedder.duplicates();
+		assert_eq!(
+			duplicates.iter().map(|duplicate| duplicate.name).collect::<Vec<_>>(),
+			[
+				"LINKSTORE_BIG",
+				"LINKSTORE_SHORTS",
+				"LINKSTORE_TEST",
+				"LINKSTORE_YEAH",
+				"linkstore_tests::app.net.port"
+			]
+		);
+		assert!(duplicates.iter().all(|duplicate| duplicate.occurrences == 2 && duplicate.sizes_agree));
+		assert_eq!(
+			duplicates.iter().filter(|duplicate| !duplicate.agree()).collect::<Vec<_>>(),
+			[&Duplicate {
+				name: "LINKSTORE_YEAH",
+				occurrences: 2,
+				sizes_agree: true,
+				values_agree: false,
+			}]
+		);
+
+		assert_eq!(unsafe { embedder.read_unique::<u64>("LINKSTORE_TEST") }.unwrap(), 69);
+		assert!(matches!(
+			unsafe { embedder.read_unique::<u32>("LINKSTORE_YEAH") },
+			Err(Error::DivergentDuplicates(name)) if name == "LINKSTORE_YEAH"
+		));
+		assert!(matches!(
+			unsafe { embedder.read_unique::<u32>("LINKSTORE_TEST") },
+			Err(Error::MismatchedSize(8, 4))
+		));
+
+		// Embedding a single value makes the occurrences agree again
+		embedder.embed("LINKSTORE_YEAH", &420_u32).unwrap();
+		assert!(embedder.duplicates().iter().all(Duplicate::agree));
+		assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_YEAH") }.unwrap(), 420);
+	}
+
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	assert!(matches!(
+		Embedder::builder().strict(true).build(&mut cursor),
+		Err(Error::DivergentDuplicates(name)) if name == "LINKSTORE_YEAH"
+	));
+
+	// Occurrences of different sizes disagree too
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry("LINKSTORE_YEAH", &0_u32)
+		.entry("LINKSTORE_YEAH", &0_u64)
+		.build();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		assert!(matches!(
+			embedder.duplicates()[..],
+			[Duplicate {
+				name: "LINKSTORE_YEAH",
+				occurrences: 2,
+				sizes_agree: false,
+				..
+			}]
+		));
+	}
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	assert!(matches!(
+		Embedder::builder().strict(true).build(&mut cursor),
+		Err(Error::DivergentDuplicates(_))
+	));
+
+	// Duplicates which agree are allowed in strict mode
+	let mut binary = fixture(Format::MachO, PointerWidth::Bits64, 69, 420, [1, 2, 3, 4], u128::MAX / 2)
+		.fat(true)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::builder().strict(true).build(&mut cursor).unwrap();
+	assert_eq!(unsafe { embedder.read_unique::<u32>("LINKSTORE_YEAH") }.unwrap(), 420);
+}
+
+#[test]
+fn embed_where() {
+	let unstamped = fixture(Format::MachO, PointerWidth::Bits64, 69, 420, [1, 2, 3, 4], u128::MAX / 2)
+		.fat(true)
+		.build();
+
+	for arch in [Architecture::X86_64, Architecture::Aarch64] {
+		let mut binary = unstamped.clone();
+		let (selected, other) = {
+			let mut cursor = std::io::Cursor::new(&mut binary[..]);
+			let mut embedder = Embedder::new(&mut cursor).unwrap();
+			let (selected, other): (Vec<_>, Vec<_>) = embedder
+				.locations("LINKSTORE_YEAH")
+				.unwrap()
+				.cloned()
+				.partition(|location| location.arch == arch);
+			assert_eq!((selected.len(), other.len()), (1, 1), "{arch:?}");
+
+			embedder
+				.embed_where("LINKSTORE_YEAH", &1337_u32, |location| location.arch == arch)
+				.unwrap();
+			embedder.finish().unwrap();
+			(selected[0].offset as usize, other[0].offset as usize)
+		};
+
+		// Only the selected slice's value changes
+		let changed = (0..binary.len()).filter(|&i| binary[i] != unstamped[i]).collect::<Vec<_>>();
+		assert!(changed.iter().all(|i| (selected..selected + 4).contains(i)), "{arch:?}");
+		assert_eq!(binary[selected..selected + 4], 1337_u32.to_le_bytes(), "{arch:?}");
+		assert_eq!(binary[other..other + 4], 420_u32.to_le_bytes(), "{arch:?}");
+	}
+
+	// A filter which matches nothing embeds nothing
+	let mut binary = unstamped.clone();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		embedder
+			.embed_where("LINKSTORE_YEAH", &1337_u32, |location| location.arch == Architecture::Unknown)
+			.unwrap();
+		assert!(matches!(
+			embedder.embed_where("LINKSTORE_MISSING", &1337_u32, |_| false),
+			Err(Error::NotPresent(_))
+		));
+		embedder.finish().unwrap();
+	}
+	assert!(binary == unstamped);
+
+	// Nothing is embedded if the value doesn't fit every matching occurrence, even those before the one that fails
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry("LINKSTORE_YEAH", &420_u32)
+		.entry("LINKSTORE_YEAH", &420_u64)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	let before = embedder.export();
+	assert!(matches!(
+		embedder.embed_where("LINKSTORE_YEAH", &1337_u32, |_| true),
+		Err(Error::MismatchedSize(8, 4))
+	));
+	assert_eq!(embedder.export(), before);
+}
+
+#[test]
+fn locations() {
+	use PointerWidth::*;
+
+	// ELF and Mach-O segments map the file from the base address, while the PE section and wasm data segment are placed at fixed addresses
+	#[rustfmt::skip]
+	let binaries = [
+		(Format::Elf, Bits64, false, 0x40_0000, true, None),
+		(Format::Elf, Bits32, false, 0x40_0000, true, None),
+		(Format::Pe, Bits64, false, 0x1_4000_1000, false, None),
+		(Format::Pe, Bits32, false, 0x40_1000, false, None),
+		(Format::MachO, Bits64, false, 0x1_0000_0000, true, Some("__TEXT")),
+		(Format::MachO, Bits32, false, 0x1000, true, Some("__TEXT")),
+		(Format::MachO, Bits64, true, 0x1_0000_0000, true, Some("__TEXT")),
+		(Format::Wasm, Bits32, false, 1024, false, None),
+	];
+
+	for (format, pointer_width, fat, base, mapped, segment) in binaries {
+		let context = format!("{format:?} {pointer_width:?} (fat: {fat})");
+
+		let mut binary = fixture(format, pointer_width, 69, 420, [1, 2, 3, 4], u128::MAX / 2).fat(fat).build();
+
+		// The section begins with the first container, in each slice of a fat binary
+		let slices = if fat {
+			[16, 36]
+				.map(|field| u32::from_be_bytes(binary[field..field + 4].try_into().unwrap()) as u64)
+				.to_vec()
+		} else {
+			vec![0]
+		};
+		let sections = slices
+			.iter()
+			.map(|&slice| {
+				let start = slice as usize;
+				start
+					+ binary[start..]
+						.windows(embed::encode::MAGIC.len())
+						.position(|window| window == embed::encode::MAGIC)
+						.unwrap()
+			})
+			.collect::<Vec<_>>();
+
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		for name in embedder.keys() {
+			let locations = embedder.locations(name).unwrap().collect::<Vec<_>>();
+			assert_eq!(locations.len(), slices.len(), "{context} {name}");
+
+			for ((location, slice), section) in locations.into_iter().zip(slices.iter()).zip(sections.iter()) {
+				let section = *section as u64;
+				assert_eq!(
+					location.section,
+					if format == Format::Wasm { "data[0]" } else { ".lnkstre" },
+					"{context} {name}"
+				);
+				assert_eq!(location.segment.as_deref(), segment, "{context} {name}");
+				assert!(location.offset > section, "{context} {name}");
+				assert_eq!(
+					location.virtual_address,
+					Some(base + if mapped { section - slice } else { 0 } + (location.offset - section)),
+					"{context} {name}"
+				);
+			}
+		}
+	}
+
+	// Relocatable objects haven't been assigned addresses yet
+	for format in [Format::Elf, Format::Coff, Format::MachO] {
+		let mut binary = fixture(format, Bits64, 69, 420, [1, 2, 3, 4], u128::MAX / 2).archive(true).build();
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		for location in embedder.locations("LINKSTORE_YEAH").unwrap() {
+			assert_eq!(location.virtual_address, None, "{format:?}");
+			assert_eq!(location.section, ".lnkstre", "{format:?}");
+			assert_eq!(location.segment.is_some(), format == Format::MachO, "{format:?}");
+			assert!(location.member.is_some(), "{format:?}");
+		}
+	}
+}
+
+#[test]
+fn section_names() {
+	// Returns the sections the key was found in by an embedder searching the given sections
+	let find = |binary: &testing::SyntheticBinary, section_names: &[&str]| {
+		let mut binary = binary.build();
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let builder = section_names
+			.iter()
+			.fold(Embedder::builder(), |builder, name| builder.section_name(*name));
+		let embedder = builder.build(&mut cursor).unwrap();
+		embedder.locations("LINKSTORE_YEAH").map_or_else(
+			|_| Vec::new(),
+			|locations| locations.map(|location| (location.segment.clone(), location.section.clone())).collect(),
+		)
+	};
+	let binary = |format: Format, section_name: &str| {
+		testing::SyntheticBinary::new(format, PointerWidth::Bits64)
+			.entry("LINKSTORE_YEAH", &420_u32)
+			.section_name(section_name)
+	};
+
+	let elf = binary(Format::Elf, ".lnkcfg");
+	assert_eq!(find(&elf, &[]), []);
+	assert_eq!(find(&elf, &[".lnkstre", ".lnkcfg"]), [(None, ".lnkcfg".to_string())]);
+
+	// `segment,section` names only match the section in that segment, whilst plain names match it in any segment
+	let macho = binary(Format::MachO, "__DATA,__mycfg");
+	let found = [(Some("__DATA".to_string()), "__mycfg".to_string())];
+	assert_eq!(find(&macho, &[]), []);
+	assert_eq!(find(&macho, &["__DATA,__mycfg"]), found);
+	assert_eq!(find(&macho, &["__mycfg"]), found);
+	assert_eq!(find(&macho, &["__TEXT,__mycfg"]), []);
+	assert_eq!(find(&macho, &["__DATA,__other"]), []);
+	assert_eq!(find(&macho.clone().fat(true), &["__DATA,__mycfg"]), [found.clone(), found].concat());
+
+	// PE images truncate section names to 8 bytes, so longer names match their truncated form
+	let pe = binary(Format::Pe, ".lnkconfig");
+	assert_eq!(find(&pe, &[]), []);
+	assert_eq!(find(&pe, &[".lnkconfig"]), [(None, ".lnkconf".to_string())]);
+	assert_eq!(find(&pe, &[".lnkconf"]), [(None, ".lnkconf".to_string())]);
+	assert_eq!(find(&pe, &[".lnkcon"]), []);
+	assert_eq!(find(&binary(Format::Pe, ".lnkcfg"), &[".lnkcfg"]), [(None, ".lnkcfg".to_string())]);
+
+	// COFF objects keep longer names in their string table
+	let coff = binary(Format::Coff, ".lnkconfig");
+	assert_eq!(find(&coff, &[".lnkconfig"]), [(None, ".lnkconfig".to_string())]);
+	assert_eq!(find(&coff, &[".lnkconf"]), []);
+}
+
+#[test]
+fn pool() {
+	const POOL: &str = "linkstore::pool::POOL";
+
+	let pointer_width = if cfg!(target_pointer_width = "64") {
+		PointerWidth::Bits64
+	} else {
+		PointerWidth::Bits32
+	};
+	let binary = |pool: &[u8], fat: bool| {
+		let mut contents = pool.to_vec();
+		contents.resize(128, 0);
+		testing::SyntheticBinary::new(Format::MachO, pointer_width)
+			.entry("LINKSTORE_YEAH", &0xDEADBEEF_u32)
+			.entry_bytes(POOL, contents, 1)
+			.fat(fat)
+			.build()
+	};
+
+	let build = testing::container("build.number", &1337_u32.to_le_bytes(), pointer_width, 1);
+	let channel = testing::container("channel", b"beta", pointer_width, 1);
+
+	let number = 1337_u32.to_le_bytes();
+	for fat in [false, true] {
+		let mut stamped = binary(&[], fat);
+		{
+			let mut cursor = std::io::Cursor::new(&mut stamped[..]);
+			let mut embedder = Embedder::new(&mut cursor).unwrap();
+			assert_eq!(embedder.keys().collect::<Vec<_>>(), ["LINKSTORE_YEAH"]);
+
+			embedder.insert("build.number", &number).unwrap();
+			assert!(matches!(embedder.insert("build.number", &[0]), Err(Error::AlreadyPresent(_))));
+			assert!(matches!(embedder.insert("LINKSTORE_YEAH", &[0]), Err(Error::AlreadyPresent(_))));
+			assert!(matches!(embedder.insert(POOL, &[0]), Err(Error::InvalidName(_))));
+			assert!(matches!(embedder.insert("linkstore::pool::OTHER", &[0]), Err(Error::InvalidName(_))));
+			assert!(matches!(
+				embedder.insert("linkstore::provenance::RECORD", &[0]),
+				Err(Error::InvalidName(_))
+			));
+			assert!(matches!(
+				embedder.insert("linkstore::default::build.number", &[0]),
+				Err(Error::InvalidName(_))
+			));
+			assert!(matches!(
+				embedder.insert(&"a".repeat(embed::encode::MAX_NAME_LEN + 1), &[0]),
+				Err(Error::InvalidName(_))
+			));
+			assert!(matches!(embedder.insert("too.big", &[0; 128]), Err(Error::PoolFull(_, _))));
+
+			assert_eq!(embedder.locations("build.number").unwrap().count(), if fat { 2 } else { 1 });
+			assert_eq!(unsafe { embedder.read_unique::<u32>("build.number") }.unwrap(), 1337);
+			embedder.finish().unwrap();
+		}
+		assert!(stamped == binary(&build, fat));
+
+		// Inserted keys are discovered like any other, and new keys are allocated after them
+		{
+			let mut cursor = std::io::Cursor::new(&mut stamped[..]);
+			let mut embedder = Embedder::new(&mut cursor).unwrap();
+			assert_eq!(unsafe { embedder.read_unique::<u32>("build.number") }.unwrap(), 1337);
+			embedder.insert("channel", b"beta").unwrap();
+			embedder.finish().unwrap();
+		}
+		assert!(stamped == binary(&[&build[..], &channel].concat(), fat));
+	}
+
+	// Names which only share a prefix with a reserved namespace aren't reserved
+	let mut binary = binary(&[], false);
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		embedder.insert("linkstore::poolside::x", &number).unwrap();
+		embedder.insert("linkstore::provenanced::x", &number).unwrap();
+		embedder.finish().unwrap();
+	}
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	assert_eq!(unsafe { embedder.read_unique::<u32>("linkstore::poolside::x") }.unwrap(), 1337);
+	assert_eq!(unsafe { embedder.read_unique::<u32>("linkstore::provenanced::x") }.unwrap(), 1337);
+
+	let contents = [&build[..], &channel, &[0; 16]].concat();
+	assert_eq!(pool::find(&contents, "build.number"), Some(&1337_u32.to_le_bytes()[..]));
+	assert_eq!(pool::find(&contents, "channel"), Some(&b"beta"[..]));
+	assert_eq!(pool::find(&contents, "missing"), None);
+
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, pointer_width)
+		.entry("LINKSTORE_YEAH", &0xDEADBEEF_u32)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	assert!(matches!(
+		Embedder::new(&mut cursor).unwrap().insert("build.number", &[0]),
+		Err(Error::NoPool)
+	));
+}
+
+#[test]
+fn lookup() {
+	linkstore! {
+		namespace = "lookup";
+		keep_defaults = true;
+
+		static LINKSTORE_RETRIES: u32 = 3;
+	}
+	linkstore! {
+		namespace = "lookup";
+		section = ".lkcustom";
+		macho_section = "__DATA,__lkcustom";
+
+		static LINKSTORE_LIMIT: u64 = 0xDEADBEEF;
+	}
+
+	assert_eq!(get_raw(LINKSTORE_RETRIES::NAME), Some(&3_u32.to_le_bytes()[..]));
+	assert_eq!(get_raw(LINKSTORE_RETRIES::DEFAULT_NAME), Some(&3_u32.to_le_bytes()[..]));
+	assert_eq!(unsafe { get_by_name::<u32>(LINKSTORE_RETRIES::NAME) }, Some(3));
+	assert_eq!(unsafe { get_by_name::<u64>(LINKSTORE_LIMIT::NAME) }, Some(0xDEADBEEF));
+
+	// Keys defined elsewhere in the test binary are found too
+	assert_eq!(get_raw("over_aligned::BLOCK16").map(<[u8]>::len), Some(16));
+
+	assert_eq!(unsafe { get_by_name::<u16>(LINKSTORE_RETRIES::NAME) }, None);
+	assert_eq!(get_raw("lookup::LINKSTORE_MISSING"), None);
+
+	// Every container in a region is indexed, and the first occurrence of a key is the one returned
+	let pointer_width = if cfg!(target_pointer_width = "64") {
+		PointerWidth::Bits64
+	} else {
+		PointerWidth::Bits32
+	};
+	let binary = fixture(Format::Elf, pointer_width, 69, 420, [1, 2, 3, 4], u128::MAX / 2)
+		.entry("LINKSTORE_TEST", &0_u64)
+		.build();
+	let index = lookup::index([&binary[..]]);
+	assert_eq!(index["LINKSTORE_TEST"][..], 69_u64.to_le_bytes());
+	assert_eq!(index["linkstore_tests::app.net.port"][..], 8080_u16.to_le_bytes());
+
+	// Keys inserted into a pool are found inside the pool's container
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, pointer_width)
+		.entry("LINKSTORE_YEAH", &420_u32)
+		.entry_bytes("linkstore::pool::POOL", [0; 128], 1)
+		.build();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		let number = 1337_u32.to_le_bytes();
+		embedder.insert("build.number", &number).unwrap().insert("channel", b"beta").unwrap();
+		embedder.finish().unwrap();
+	}
+	let index = lookup::index([&binary[..]]);
+	assert_eq!(index["build.number"][..], 1337_u32.to_le_bytes());
+	assert_eq!(index["channel"][..], *b"beta");
+	assert_eq!(index["LINKSTORE_YEAH"][..], 420_u32.to_le_bytes());
+
+	assert!(lookup::index([b"not a binary"]).is_empty());
+}
+
+#[test]
+fn try_get() {
+	linkstore! {
+		namespace = "try_get";
+
+		static ENABLED: bool = true;
+		static BYTES: [u8; 4] = [0xDE, 0xAD, 0xBE, 0xEF];
+	}
+
+	assert!(ENABLED::try_get().unwrap());
+	assert_eq!(BYTES::try_get().unwrap(), [0xDE, 0xAD, 0xBE, 0xEF]);
+	assert_eq!(unsafe { *BYTES::get() }, [0xDE, 0xAD, 0xBE, 0xEF]);
+
+	assert_eq!(bool::try_from_le_bytes(&[0]), Ok(false));
+	assert_eq!(bool::try_from_le_bytes(&[2]), Err(TryDecodeLinkstoreBoolError::Invalid(2)));
+	assert!(matches!(
+		<[bool; 2]>::try_from_le_bytes(&[1, 3]),
+		Err(TryDecodeLinkstoreArrayError::Other(TryDecodeLinkstoreBoolError::Invalid(3)))
+	));
+}
+
+#[test]
+fn pointer_sized() {
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits32)
+		.entry_bytes("LINKSTORE_LEN", 5_u32.to_le_bytes(), 4)
+		.entry_bytes("LINKSTORE_OFFSET", (-1_i32).to_le_bytes(), 4)
+		.entry_bytes("LINKSTORE_LENS", [1_u32.to_le_bytes(), 2_u32.to_le_bytes()].concat(), 4)
+		.build();
+
+	let (too_big, too_small) = (TargetUsize(u64::from(u32::MAX) + 1), TargetIsize(i64::from(i32::MIN) - 1));
+	let (max, min, lens) = (TargetUsize(u64::from(u32::MAX)), TargetIsize(i64::from(i32::MIN)), [3_usize, 4]);
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		unsafe {
+			assert_eq!(embedder.read_unique::<usize>("LINKSTORE_LEN").unwrap(), 5);
+			assert_eq!(embedder.read_unique::<TargetUsize>("LINKSTORE_LEN").unwrap(), TargetUsize(5));
+			assert_eq!(embedder.read_unique::<isize>("LINKSTORE_OFFSET").unwrap(), -1);
+			assert_eq!(embedder.read_unique::<TargetIsize>("LINKSTORE_OFFSET").unwrap(), TargetIsize(-1));
+			assert!(matches!(
+				embedder.try_read::<[usize; 2]>("LINKSTORE_LENS").unwrap().next(),
+				Some(Ok([1, 2]))
+			));
+			assert!(matches!(embedder.read::<u64>("LINKSTORE_LEN"), Err(Error::MismatchedSize(4, 8))));
+		}
+
+		assert!(matches!(embedder.embed("LINKSTORE_LEN", &too_big), Err(Error::PointerWidthOverflow(_))));
+		assert!(matches!(
+			embedder.embed("LINKSTORE_OFFSET", &too_small),
+			Err(Error::PointerWidthOverflow(_))
+		));
+		embedder.embed("LINKSTORE_LEN", &max).unwrap();
+		embedder.embed("LINKSTORE_OFFSET", &min).unwrap();
+		embedder.embed("LINKSTORE_LENS", &lens).unwrap();
+		embedder.finish().unwrap();
+	}
+
+	let expected = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits32)
+		.entry_bytes("LINKSTORE_LEN", u32::MAX.to_le_bytes(), 4)
+		.entry_bytes("LINKSTORE_OFFSET", i32::MIN.to_le_bytes(), 4)
+		.entry_bytes("LINKSTORE_LENS", [3_u32.to_le_bytes(), 4_u32.to_le_bytes()].concat(), 4)
+		.build();
+	assert!(binary == expected);
+
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry_bytes("LINKSTORE_LEN", 5_u64.to_le_bytes(), 8)
+		.build();
+	let max = TargetUsize(u64::MAX);
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	embedder.embed("LINKSTORE_LEN", &max).unwrap();
+	assert_eq!(
+		unsafe { embedder.read_unique::<TargetUsize>("LINKSTORE_LEN") }.unwrap(),
+		TargetUsize(u64::MAX)
+	);
+
+	// Arrays must be exactly as big as their elements on the target
+	assert!(matches!(
+		<[u8; 4]>::try_from_target_le_bytes(&[], 8),
+		Err(TryDecodeLinkstoreArrayError::MismatchedBytesCount(0, 1))
+	));
+	assert!(matches!(
+		<[usize; 2]>::try_from_target_le_bytes(&[0; 12], 4),
+		Err(TryDecodeLinkstoreArrayError::MismatchedBytesCount(12, 4))
+	));
+	assert!(matches!(<[[u8; 0]; 2]>::try_from_target_le_bytes(&[], 8), Ok([[], []])));
+	assert!(matches!(<[[u8; 0]; 2]>::try_from_le_bytes(&[]), Ok([[], []])));
+}
+
+macro_rules! aligned_blocks {
+	($($name:ident($align:literal)),+) => {$(
+		#[repr(C, align($align))]
+		#[derive(Debug, Clone, Copy, PartialEq)]
+		struct $name([u8; $align]);
+
+		unsafe impl EncodeLinkstore for $name {
+			fn as_le_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+				std::borrow::Cow::Borrowed(&self.0)
+			}
+		}
+
+		unsafe impl TryDecodeLinkstore for $name {
+			type Error = core::convert::Infallible;
+
+			fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+				Ok(Self(bytes.try_into().unwrap()))
+			}
+		}
+	)+};
+}
+aligned_blocks!(Block16(16), Block32(32), Block64(64));
+
+#[test]
+fn over_aligned() {
+	linkstore! {
+		namespace = "over_aligned";
+
+		static BLOCK16: Block16 = Block16([16; 16]);
+		static BLOCK32: Block32 = Block32([32; 32]);
+		static BLOCK64: Block64 = Block64([64; 64]);
+	}
+
+	assert_eq!(BLOCK16::try_get(), Ok(Block16([16; 16])));
+	assert_eq!(BLOCK32::try_get(), Ok(Block32([32; 32])));
+	assert_eq!(BLOCK64::try_get(), Ok(Block64([64; 64])));
+	assert_eq!(&BLOCK64::__LINKSTORE_CONTAINER.value as *const _ as usize % 64, 0);
+
+	// The header of each container emitted by the compiler describes where its value is
+	let mut binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	unsafe {
+		assert_eq!(
+			embedder.try_read::<Block16>(BLOCK16::NAME).unwrap().next().unwrap(),
+			Ok(Block16([16; 16]))
+		);
+		assert_eq!(
+			embedder.try_read::<Block32>(BLOCK32::NAME).unwrap().next().unwrap(),
+			Ok(Block32([32; 32]))
+		);
+		assert_eq!(
+			embedder.try_read::<Block64>(BLOCK64::NAME).unwrap().next().unwrap(),
+			Ok(Block64([64; 64]))
+		);
+	}
+	for (name, align) in [(BLOCK16::NAME, 16), (BLOCK32::NAME, 32), (BLOCK64::NAME, 64)] {
+		for location in embedder.locations(name).unwrap() {
+			assert_eq!(location.virtual_address.unwrap() % align, 0, "{name}");
+		}
+	}
+
+	let values = (Block16([1; 16]), Block32([2; 32]), Block64([3; 64]));
+	for format in [Format::Elf, Format::Pe, Format::MachO] {
+		let fixture = |blocks: &(Block16, Block32, Block64)| {
+			testing::SyntheticBinary::new(format, PointerWidth::Bits64)
+				.entry("LINKSTORE_BYTE", &0_u8)
+				.entry("BLOCK16", &blocks.0)
+				.entry("BLOCK32", &blocks.1)
+				.entry("BLOCK64", &blocks.2)
+				.build()
+		};
+
+		let mut binary = fixture(&(Block16([0; 16]), Block32([0; 32]), Block64([0; 64])));
+		{
+			let mut cursor = std::io::Cursor::new(&mut binary[..]);
+			let mut embedder = Embedder::new(&mut cursor).unwrap();
+			embedder.embed("BLOCK16", &values.0).unwrap();
+			embedder.embed("BLOCK32", &values.1).unwrap();
+			embedder.embed("BLOCK64", &values.2).unwrap();
+			embedder.finish().unwrap();
+		}
+		assert!(binary == fixture(&values), "{format:?}");
+	}
+}
+
+#[test]
+fn defaults() {
+	linkstore! {
+		namespace = "defaults";
+		keep_defaults = true;
+
+		static LINKSTORE_PORT: u16 = 8080;
+	}
+
+	assert_eq!(LINKSTORE_PORT::DEFAULT_NAME, "linkstore::default::defaults::LINKSTORE_PORT");
+	assert!(LINKSTORE_PORT::is_default());
+
+	let port = 9090_u16;
+	let mut binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		assert!(embedder.keys().all(|name| !name.starts_with("linkstore::default::")));
+		assert_eq!(unsafe { embedder.default_value::<u16>(LINKSTORE_PORT::NAME) }.unwrap(), 8080);
+		assert!(!embedder.is_modified(LINKSTORE_PORT::NAME).unwrap());
+
+		embedder.embed(LINKSTORE_PORT::NAME, &port).unwrap();
+		assert!(embedder.is_modified(LINKSTORE_PORT::NAME).unwrap());
+		embedder.finish().unwrap();
+	}
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::new(&mut cursor).unwrap();
+		assert_eq!(unsafe { embedder.read_unique::<u16>(LINKSTORE_PORT::NAME) }.unwrap(), 9090);
+		assert_eq!(unsafe { embedder.default_value::<u16>(LINKSTORE_PORT::NAME) }.unwrap(), 8080);
+		assert!(embedder.is_modified(LINKSTORE_PORT::NAME).unwrap());
+
+		embedder.reset(LINKSTORE_PORT::NAME).unwrap();
+		assert!(!embedder.is_modified(LINKSTORE_PORT::NAME).unwrap());
+		embedder.finish().unwrap();
+	}
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	assert_eq!(unsafe { embedder.read_unique::<u16>(LINKSTORE_PORT::NAME) }.unwrap(), 8080);
+
+	// Keys defined without `keep_defaults = true;` have no default to compare against
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry("LINKSTORE_PORT", &8080_u16)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	assert!(matches!(embedder.is_modified("LINKSTORE_PORT"), Err(Error::NoDefault(_))));
+	assert!(matches!(embedder.reset("LINKSTORE_PORT"), Err(Error::NoDefault(_))));
+	assert!(matches!(
+		embedder.insert("linkstore::default::LINKSTORE_PORT", &[]),
+		Err(Error::InvalidName(_))
+	));
+}
+
+/// Serialises tests which set environment variables, as the environment is shared by every test running in parallel.
+fn lock_env() -> std::sync::MutexGuard<'static, ()> {
+	static ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+	ENV.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[test]
+fn provenance() {
+	linkstore_provenance!(PROVENANCE);
+	linkstore! {
+		namespace = "provenance";
+
+		static LINKSTORE_BUILD: u32 = 1;
+	}
+
+	assert_eq!(PROVENANCE::NAME, "linkstore::provenance::PROVENANCE");
+	assert_eq!(PROVENANCE::try_get(), Ok(Provenance::UNSTAMPED));
+	assert!(!PROVENANCE::try_get().unwrap().is_stamped());
+
+	let _env = lock_env();
+	std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+
+	let original = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+	let stamp = |binary: &mut Vec<u8>| {
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::builder().tool("linkstore tests").build(&mut cursor).unwrap();
+		assert!(embedder.keys().all(|name| !name.starts_with("linkstore::provenance")));
+
+		let mut manifest = embedder.export();
+		manifest.entries.retain(|entry| entry.name == LINKSTORE_BUILD::NAME);
+		manifest.entries[0]
+			.occurrences
+			.iter_mut()
+			.for_each(|occurrence| occurrence.bytes = 2_u32.to_le_bytes().to_vec());
+		embedder.import(&manifest).unwrap();
+		embedder.finish().unwrap();
+	};
+
+	let mut binary = original.clone();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		assert_eq!(embedder.provenance().unwrap(), Provenance::UNSTAMPED);
+	}
+	stamp(&mut binary);
+
+	let provenance = {
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::new(&mut cursor).unwrap();
+		embedder.provenance().unwrap()
+	};
+	assert!(provenance.is_stamped());
+	assert_eq!(provenance.timestamp(), 1700000000);
+	assert_eq!(provenance.version(), env!("CARGO_PKG_VERSION"));
+	assert_eq!(provenance.tool(), "linkstore tests");
+	assert!(provenance.manifest_hash().is_some());
+
+	// The values hash only depends on the values, so restamping with the same values produces the same record
+	let stamped = binary.clone();
+	stamp(&mut binary);
+	assert!(binary == stamped);
+
+	// Binaries which are only read aren't stamped
+	let mut binary = original.clone();
+	Embedder::new(&mut std::io::Cursor::new(&mut binary[..])).unwrap().finish().unwrap();
+	assert!(binary == original);
+
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	embedder.embed(LINKSTORE_BUILD::NAME, &1_u32).unwrap();
+	embedder.finish().unwrap();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let embedder = Embedder::new(&mut cursor).unwrap();
+	assert!(embedder.provenance().unwrap().is_stamped());
+	assert_ne!(embedder.provenance().unwrap().values_hash(), provenance.values_hash());
+	assert_eq!(embedder.provenance().unwrap().manifest_hash(), None);
+	assert_eq!(embedder.provenance().unwrap().tool(), "");
+
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.entry("LINKSTORE_TEST", &0_u64)
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	assert!(matches!(embedder.provenance(), Err(Error::NoProvenance)));
+	assert!(matches!(
+		embedder.insert("linkstore::provenance::PROVENANCE", &[]),
+		Err(Error::InvalidName(_))
+	));
+
+	// Reading a signed binary doesn't invalidate its signature by stamping it
+	let original = testing::SyntheticBinary::new(Format::Pe, PointerWidth::Bits64)
+		.signed(true)
+		.entry("linkstore::provenance::PROVENANCE", &Provenance::UNSTAMPED)
+		.build();
+	let mut binary = original.clone();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let embedder = Embedder::builder().signature_policy(SignaturePolicy::Refuse).build(&mut cursor).unwrap();
+	assert!(embedder.provenance().is_ok());
+	assert_eq!(embedder.finish_with_report().unwrap(), FinishReport::default());
+	assert!(binary == original);
+}
+
+#[test]
+fn build_id() {
+	linkstore! {
+		namespace = "build_id";
+
+		static LINKSTORE_BUILD: u32 = 1;
+	}
+
+	// The test binary contains a provenance record, which would otherwise be stamped with the current time
+	let _env = lock_env();
+	std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+
+	let original = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+	let stamp = |build: u32, update_build_id: bool| {
+		let mut binary = original.clone();
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::builder().update_build_id(update_build_id).build(&mut cursor).unwrap();
+		embedder.embed(LINKSTORE_BUILD::NAME, &build).unwrap();
+		let report = embedder.finish_with_report().unwrap();
+		(binary, report)
+	};
+
+	let (binary, report) = stamp(2, false);
+	assert!(report.build_ids.is_empty());
+	assert!(binary.len() == original.len());
+
+	// Binaries which are only read keep their identifiers
+	let mut binary = original.clone();
+	{
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let embedder = Embedder::builder().update_build_id(true).build(&mut cursor).unwrap();
+		assert!(embedder.finish_with_report().unwrap().build_ids.is_empty());
+	}
+	assert!(binary == original);
+
+	let (binary, report) = stamp(2, true);
+	for update in report.build_ids.iter() {
+		let offset = update.offset as usize;
+		assert_eq!(&original[offset..offset + update.old.len()], update.old);
+		assert_eq!(&binary[offset..offset + update.new.len()], update.new);
+		assert_ne!(update.old, update.new);
+	}
+
+	// Identifiers only depend on the stamped contents
+	let (restamped, restamped_report) = stamp(2, true);
+	assert!(restamped == binary);
+	assert_eq!(restamped_report, report);
+
+	let (_, other) = stamp(3, true);
+	assert_eq!(other.build_ids.len(), report.build_ids.len());
+	for (other, update) in other.build_ids.iter().zip(report.build_ids.iter()) {
+		assert_ne!(other.new, update.new);
+	}
+
+	// Build IDs are the SHA-1 hash of the binary with them zeroed, as linkers compute them
+	let mut zeroed = binary.clone();
+	for update in report.build_ids.iter() {
+		zeroed[update.offset as usize..][..update.new.len()].fill(0);
+	}
+	for update in report.build_ids.iter().filter(|update| update.kind == BuildIdKind::GnuBuildId) {
+		let sha1 = sha1_smol::Sha1::from(&zeroed).digest().bytes();
+		assert_eq!(update.new, sha1[..update.new.len().min(sha1.len())]);
+
+		let hex = |bytes: &[u8]| bytes.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+		let path = std::path::Path::new(".build-id")
+			.join(hex(&update.new[..1]))
+			.join(format!("{}.debug", hex(&update.new[1..])));
+		assert_eq!(update.new_debug_path(), Some(path));
+		assert_ne!(update.old_debug_path(), update.new_debug_path());
+	}
+
+	// Debug files split off before stamping keep the old identifiers until they're updated
+	let mut debug = original.clone();
+	for update in report.build_ids.iter() {
+		assert!(update.update_debug_file(&mut std::io::Cursor::new(&mut debug[..])).unwrap());
+		assert_eq!(&debug[update.offset as usize..][..update.new.len()], update.new);
+		assert!(!update.update_debug_file(&mut std::io::Cursor::new(&mut debug[..])).unwrap());
+
+		// Malformed debug files are rejected like malformed binaries, rather than panicking
+		let ar_header = format!("{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n", "//", 0, 0, 0, 644, 0);
+		let mut malformed = [&b"!<arch>\n"[..], ar_header.as_bytes()].concat();
+		assert!(update.update_debug_file(&mut std::io::Cursor::new(&mut malformed[..])).is_err());
+	}
+
+	#[cfg(any(target_os = "linux", target_os = "macos"))]
+	assert_eq!(
+		report.build_ids.first().map(|update| update.kind),
+		Some(if cfg!(target_os = "macos") {
+			BuildIdKind::MachOUuid
+		} else {
+			BuildIdKind::GnuBuildId
+		})
+	);
+}
+
+#[test]
+fn signatures() {
+	let fixture = |format: Format, pointer_width: PointerWidth, fat: bool, signed: bool, value: u32| {
+		testing::SyntheticBinary::new(format, pointer_width)
+			.symbols(true)
+			.fat(fat)
+			.signed(signed)
+			.entry("LINKSTORE_BUILD", &value)
+			.build()
+	};
+	let stamp = |binary: &mut Vec<u8>, policy: SignaturePolicy| {
+		let mut cursor = std::io::Cursor::new(&mut binary[..]);
+		let mut embedder = Embedder::builder().signature_policy(policy).build(&mut cursor).unwrap();
+		embedder.embed("LINKSTORE_BUILD", &2_u32).unwrap();
+		embedder.finish_with_report()
+	};
+
+	for (format, kind) in [
+		(Format::Pe, SignatureKind::Authenticode),
+		(Format::MachO, SignatureKind::MachOCodeSignature),
+	] {
+		for pointer_width in [PointerWidth::Bits32, PointerWidth::Bits64] {
+			for fat in [false, true] {
+				let slices = if fat && format == Format::MachO { 2 } else { 1 };
+				let original = fixture(format, pointer_width, fat, true, 1);
+
+				let mut binary = original.clone();
+				{
+					let mut cursor = std::io::Cursor::new(&mut binary[..]);
+					let embedder = Embedder::builder().signature_policy(SignaturePolicy::Refuse).build(&mut cursor).unwrap();
+					assert_eq!(embedder.signatures().filter(|signature| signature.kind == kind).count(), slices);
+
+					// Nothing is written, so nothing is invalidated
+					assert_eq!(embedder.finish_with_report().unwrap(), FinishReport::default());
+				}
+
+				assert!(matches!(stamp(&mut binary, SignaturePolicy::Refuse), Err(Error::Signed)));
+				assert!(binary == original);
+
+				let report = stamp(&mut binary, SignaturePolicy::Warn).unwrap();
+				assert_eq!(report.invalidated_signatures.len(), slices);
+				assert!(report.stripped_signatures.is_empty());
+				assert!(binary == fixture(format, pointer_width, fat, true, 2));
+
+				let mut binary = original.clone();
+				let report = stamp(&mut binary, SignaturePolicy::Strip).unwrap();
+				assert_eq!(report.stripped_signatures.len(), slices);
+				assert!(report.invalidated_signatures.is_empty());
+
+				{
+					let mut cursor = std::io::Cursor::new(&mut binary[..]);
+					let mut embedder = Embedder::new(&mut cursor).unwrap();
+					assert_eq!(embedder.signatures().count(), 0);
+					for value in unsafe { embedder.read::<u32>("LINKSTORE_BUILD") }.unwrap() {
+						assert_eq!(value, 2);
+					}
+				}
+
+				// The certificate table is left at the end of the image, but is no longer referenced
+				if format == Format::Pe {
+					let unsigned = fixture(format, pointer_width, fat, false, 2);
+					assert!(binary[..unsigned.len()] == unsigned[..]);
+				}
+			}
+		}
+	}
+}
+
+#[test]
+fn verify() {
+	let mut binary = testing::SyntheticBinary::new(Format::Elf, PointerWidth::Bits64)
+		.symbols(true)
+		.entry("LINKSTORE_PORT", &8080_u16)
+		.entry("LINKSTORE_RETRIES", &3_u32)
+		.entry("LINKSTORE_LEFTOVER", &0_u8)
+		.build();
+
+	let expected = [
+		ExpectedKey::of::<u16>("LINKSTORE_PORT"),
+		ExpectedKey::of::<u64>("LINKSTORE_RETRIES"),
+		ExpectedKey::new("LINKSTORE_STRIPPED", 4),
+	];
+
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let embedder = Embedder::new(&mut cursor).unwrap();
+
+	let verification = embedder.verify(&expected);
+	assert!(!verification.is_ok());
+	assert_eq!(verification.missing, ["LINKSTORE_STRIPPED"]);
+	assert_eq!(
+		verification.mismatched,
+		[SizeMismatch {
+			name: "LINKSTORE_RETRIES".to_string(),
+			expected: 8,
+			actual: 4,
+		}]
+	);
+	assert_eq!(verification.unexpected, ["LINKSTORE_LEFTOVER"]);
+
+	let message = verification.to_string();
+	assert!(message.contains("missing: LINKSTORE_STRIPPED"), "{message}");
+	assert!(
+		message.contains("size mismatch: LINKSTORE_RETRIES is 4 bytes, expected 8 bytes"),
+		"{message}"
+	);
+	assert!(message.contains("unexpected: LINKSTORE_LEFTOVER"), "{message}");
+	assert!(std::panic::catch_unwind(|| verification.assert_ok()).is_err());
+
+	let expected = [
+		ExpectedKey::of::<u16>("LINKSTORE_PORT"),
+		ExpectedKey::of::<u32>("LINKSTORE_RETRIES"),
+		ExpectedKey::of::<u8>("LINKSTORE_LEFTOVER"),
+	];
+	embedder.verify(&expected).assert_ok();
+
+	linkstore! {
+		namespace = "verify";
+
+		static LINKSTORE_TIMEOUT: u32 = 30;
+	}
+	assert_eq!(*unsafe { LINKSTORE_TIMEOUT::get() }, 30);
+
+	let verification = crate::verify(std::env::current_exe().unwrap(), &[ExpectedKey::of::<u32>(LINKSTORE_TIMEOUT::NAME)]).unwrap();
+	assert!(verification.missing.is_empty());
+	assert!(verification.mismatched.is_empty());
+	assert!(!verification.unexpected.iter().any(|name| name == LINKSTORE_TIMEOUT::NAME));
+}
+
+/// Asserts that a tuple encodes to the same bytes as the host's representation of it with its padding zeroed, and decodes back to itself.
+macro_rules! assert_tuple_layout {
+	($($idx:tt: $value:expr),+) => {{
+		let value = ($($value,)+);
+
+		fn same_type<T>(_: &T, _: &core::mem::MaybeUninit<T>) {}
+
+		// Moving a `MaybeUninit` doesn't preserve the padding of its value, so it's zeroed in place
+		let mut host = core::mem::MaybeUninit::uninit();
+		same_type(&value, &host);
+		let host = unsafe {
+			let ptr: *mut _ = host.as_mut_ptr();
+			ptr.write_bytes(0, 1);
+			// Writing the whole tuple could leave its padding uninitialised, so the fields are written individually over zeroed padding instead
+			$(core::ptr::addr_of_mut!((*ptr).$idx).write(value.$idx);)+
+			core::slice::from_raw_parts(ptr as *const u8, core::mem::size_of_val(&value)).to_vec()
+		};
+
+		assert_eq!(value.as_le_bytes(), host, "{value:?}");
+		assert_eq!(TryDecodeLinkstore::try_from_le_bytes(&host).ok(), Some(value), "{value:?}");
+	}};
+}
+
+#[test]
+fn composite_layout() {
+	// xorshift64, so that the values are different on every iteration without pulling in a property testing framework
+	let mut state = 0x2545F4914F6CDD1D_u64;
+	let mut next = move || {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state
+	};
+
+	for _ in 0..256 {
+		let (a, b, c, d) = (next(), next(), next(), next());
+
+		assert_tuple_layout!(0: a as u8);
+		assert_tuple_layout!(0: a as u8, 1: b);
+		assert_tuple_layout!(0: a as u8, 1: b as u16, 2: c as u32);
+		assert_tuple_layout!(0: a & 1 == 0, 1: (u128::from(b) << 64) | u128::from(c), 2: d as i8);
+		assert_tuple_layout!(0: (a as u32).to_le_bytes(), 1: b as u8, 2: [c as u16, d as u16, a as u16]);
+		assert_tuple_layout!(0: [[a as u8, b as u8], [c as u8, d as u8]], 1: b as f64, 2: c as i32);
+		assert_tuple_layout!(
+			0: a as u8, 1: b as u16, 2: c as u32, 3: d, 4: a as i8, 5: b as i16,
+			6: c as i32, 7: d as i64, 8: a as f32, 9: b as f64, 10: c & 1 == 1, 11: d as u128
+		);
+
+		// Arrays are laid out with the padding of each element
+		let array = [(a as u8, b as u32), (c as u8, d as u32), (b as u8, a as u32)];
+		let bytes = array.iter().flat_map(|elem| elem.as_le_bytes().into_owned()).collect::<Vec<_>>();
+		assert_eq!(bytes.len(), core::mem::size_of_val(&array));
+		assert_eq!(array.as_le_bytes(), bytes);
+		assert!(matches!(<[(u8, u32); 3]>::try_from_le_bytes(&bytes), Ok(decoded) if decoded == array));
+
+		let nested = [[a as u16, b as u16], [c as u16, d as u16]];
+		assert_eq!(nested.as_le_bytes()[..], unsafe {
+			core::mem::transmute::<[[u16; 2]; 2], [u8; 8]>(nested)
+		});
+	}
+
+	let error = <(u8, bool)>::try_from_le_bytes(&(0_u8, false).as_le_bytes().iter().map(|_| 2).collect::<Vec<_>>()).unwrap_err();
+	assert!(matches!(error, TryDecodeLinkstoreTupleError::Element { index: 1, .. }));
+
+	// Bytes too short for the tuple are reported rather than panicking
+	assert!(matches!(
+		<(u8, u32)>::try_from_le_bytes(&[1, 0, 0, 0, 2]),
+		Err(TryDecodeLinkstoreTupleError::MismatchedBytesCount(5, 8))
+	));
+	assert!(matches!(
+		<(u8, u32)>::try_from_le_bytes(&[]),
+		Err(TryDecodeLinkstoreTupleError::MismatchedBytesCount(0, 8))
+	));
+}
+
+#[test]
+fn foreign_layout() {
+	linkstore! {
+		namespace = "foreign_layout";
+
+		static PAIR: (u8, u32) = (1, 2);
+	}
+	assert_eq!(PAIR::try_get().ok(), Some((1, 2)));
+
+	// Tuples are read and written in binaries built for the same target as the embedder
+	let mut binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	assert_eq!(unsafe { embedder.read_unique::<(u8, u32)>(PAIR::NAME) }.unwrap(), (1, 2));
+	embedder.embed(PAIR::NAME, &(3_u8, 4_u32)).unwrap();
+
+	// ...but other targets may order and align their fields differently
+	let mut binary = testing::SyntheticBinary::new(Format::Wasm, PointerWidth::Bits32)
+		.entry("PAIR", &(1_u8, 2_u32))
+		.entry("PAIRS", &[(1_u8, 2_u32); 2])
+		.build();
+	let mut cursor = std::io::Cursor::new(&mut binary[..]);
+	let mut embedder = Embedder::new(&mut cursor).unwrap();
+	assert!(matches!(
+		unsafe { embedder.read_unique::<(u8, u32)>("PAIR") },
+		Err(Error::ForeignLayout(_))
+	));
+	assert!(matches!(embedder.embed("PAIR", &(3_u8, 4_u32)), Err(Error::ForeignLayout(_))));
+	assert!(matches!(embedder.embed("PAIRS", &[(3_u8, 4_u32); 2]), Err(Error::ForeignLayout(_))));
+	embedder.embed("PAIR", &[3_u8, 0, 0, 0, 4, 0, 0, 0]).unwrap();
+}
+
+linkstore_enum! {
+	#[repr(u16)]
+	#[derive(Debug, PartialEq)]
+	enum Mode {
+		Fast = 1,
+		Safe = 0x200,
+	}
+}
+
+#[test]
+fn validated_types() {
+	use core::num::{NonZeroI8, NonZeroU16, NonZeroU32};
+
+	linkstore! {
+		namespace = "validated_types";
+
+		static LETTER: char = 'λ';
+		static PORT: Option<core::num::NonZeroU16> = core::num::NonZeroU16::new(8080);
+		static MODE: Mode = Mode::Safe;
+	}
+
+	assert_eq!(LETTER::try_get(), Ok('λ'));
+	assert_eq!(PORT::try_get(), Ok(NonZeroU16::new(8080)));
+	assert_eq!(unsafe { *PORT::get() }, NonZeroU16::new(8080));
+	assert_eq!(MODE::try_get(), Ok(Mode::Safe));
+
+	assert_eq!(
+		char::try_from_le_bytes(&0xD800_u32.to_le_bytes()),
+		Err(TryDecodeLinkstoreCharError::Invalid(0xD800))
+	);
+	assert_eq!(
+		char::try_from_le_bytes(&0x110000_u32.to_le_bytes()),
+		Err(TryDecodeLinkstoreCharError::Invalid(0x110000))
+	);
+	assert_eq!(NonZeroU32::try_from_le_bytes(&[0; 4]), Err(TryDecodeLinkstoreNonZeroError::Zero));
+	assert_eq!(NonZeroI8::try_from_le_bytes(&[0xFF]), Ok(NonZeroI8::new(-1).unwrap()));
+	assert_eq!(<Option<NonZeroU16>>::from_le_bytes(&[0, 0]), None);
+	assert_eq!(Mode::try_from_le_bytes(&1_u16.to_le_bytes()), Ok(Mode::Fast));
+	assert_eq!(
+		Mode::try_from_le_bytes(&2_u16.to_le_bytes()),
+		Err(TryDecodeLinkstoreEnumError::Invalid(2))
+	);
+
+	// Bytes of the wrong length are reported rather than panicking
+	assert_eq!(bool::try_from_le_bytes(&[]), Err(TryDecodeLinkstoreBoolError::MismatchedBytesCount(0)));
+	assert_eq!(
+		bool::try_from_le_bytes(&[1, 0]),
+		Err(TryDecodeLinkstoreBoolError::MismatchedBytesCount(2))
+	);
+	assert_eq!(
+		char::try_from_le_bytes(&[0x41]),
+		Err(TryDecodeLinkstoreCharError::MismatchedBytesCount(1))
+	);
+	assert_eq!(
+		NonZeroU32::try_from_le_bytes(&[1, 0]),
+		Err(TryDecodeLinkstoreNonZeroError::MismatchedBytesCount(2, 4))
+	);
+	assert_eq!(
+		Mode::try_from_le_bytes(&[1]),
+		Err(TryDecodeLinkstoreEnumError::MismatchedBytesCount(1, 2))
+	);
+
+	assert_eq!(&*'λ'.as_le_bytes(), 0x3BB_u32.to_le_bytes());
+	assert_eq!(&*NonZeroU16::new(8080).as_le_bytes(), 8080_u16.to_le_bytes());
+	assert_eq!(&*None::<NonZeroU16>.as_le_bytes(), [0, 0]);
+	assert_eq!(&*Mode::Safe.as_le_bytes(), 0x200_u16.to_le_bytes());
+}
+
+#[test]
+#[cfg(feature = "env-override")]
+fn env_override() {
+	use crate::env_override::{env_var_name, ParseEnvOverride};
+
+	linkstore! {
+		namespace = "env_override";
+
+		#[linkstore(name = "app.net.port")]
+		static PORT: u16 = 8080;
+
+		static ENABLED: bool = false;
+		static KEY: [u8; 4] = [0xDE, 0xAD, 0xBE, 0xEF];
+		static SHORTS: [u16; 2] = [1, 2];
+		static FLAGS: [bool; 3] = [false; 3];
+		static TIMEOUT: u32 = 30;
+		static LETTER: char = 'a';
+	}
+
+	assert_eq!(env_var_name(PORT::NAME), "LINKSTORE_ENV_OVERRIDE__APP_NET_PORT");
+
+	let _env = lock_env();
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__APP_NET_PORT", "9090");
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__ENABLED", "true");
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__KEY", "0xcafebabe");
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__SHORTS", "3, 4");
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__FLAGS", "true,0,1");
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__TIMEOUT", "forever");
+	std::env::set_var("LINKSTORE_ENV_OVERRIDE__LETTER", "b");
+	unsafe {
+		assert_eq!(*PORT::get(), 9090);
+		assert!(ENABLED::try_get().unwrap());
+		assert_eq!(*KEY::get(), [0xCA, 0xFE, 0xBA, 0xBE]);
+		assert_eq!(*SHORTS::get(), [3, 4]);
+		assert_eq!(FLAGS::try_get().unwrap(), [true, false, true]);
+
+		// Values which can't be parsed, and types which can't be overridden, are reported and fall back to the embedded value
+		assert_eq!(*TIMEOUT::get(), 30);
+		assert_eq!(LETTER::try_get(), Ok('a'));
+
+		// The environment is only checked once
+		std::env::set_var("LINKSTORE_ENV_OVERRIDE__APP_NET_PORT", "1");
+		assert_eq!(*PORT::get(), 9090);
+	}
+
+	assert_eq!(u32::parse_env_override("0xDEADBEEF"), Some(0xDEADBEEF));
+	assert_eq!(i8::parse_env_override("-5"), Some(-5));
+	assert_eq!(f64::parse_env_override("1.5"), Some(1.5));
+	assert_eq!(u8::parse_env_override("256"), None);
+	assert_eq!(bool::parse_env_override("yes"), None);
+	assert_eq!(<[u8; 2]>::parse_env_override("abc"), None);
+	assert_eq!(<[u8; 2]>::parse_env_override("1, 255"), Some([1, 255]));
+	assert_eq!(<[u16; 2]>::parse_env_override("1,2,3"), None);
+	assert_eq!(<[i32; 2]>::parse_env_override("-1,0x10"), Some([-1, 16]));
+}
+
 macro_rules! generate_target_tests {
 	{$({
 		target_os = $target_os:literal,
@@ -187,6 +2118,7 @@ macro_rules! generate_target_tests {
 	}),*} => {
 		$(
 			#[test]
+			#[ignore = "builds the tests crate with cargo, run with `cargo test -- --ignored`"]
 			#[cfg(all(target_os = $target_os, target_arch = $target_arch, target_pointer_width = $target_pointer_width))]
 			fn linkstore() {
 				build($target_triple);
@@ -0,0 +1,58 @@
+#!/usr/bin/env bash
+# Builds the fixture binaries in `tests/fixtures/bin` from the linkstores in `tests/fixtures/src/lib.rs`.
+#
+# Each fixture is built twice, with the values it is compiled with and with the values the tests stamp it with,
+# so that the embedder's output can be compared against a binary laid out by the compiler and linker rather than by linkstore.
+#
+# Requires the targets below to be installed with rustup. Everything is linked with the `rust-lld` shipped with rustc.
+#
+# Usage: tests/fixtures/build.sh
+
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+host=$(rustc -vV | sed -n 's/^host: //p')
+lld="$(rustc --print sysroot)/lib/rustlib/$host/bin/rust-lld"
+out=bin
+work=$(mktemp -d)
+trap 'rm -rf "$work"' EXIT
+
+mkdir -p "$out"
+
+# Compiles the fixture crate into a single object file for a target
+object() {
+	local target=$1 variant=$2
+	local cfg=()
+	if [ "$variant" = stamped ]; then
+		cfg=(--cfg linkstore_stamped)
+	fi
+	mkdir -p "$work/$variant"
+	cargo rustc --quiet --release --target "$target" --target-dir "$work/target" -- --emit=obj="$work/$variant/$target.o" "${cfg[@]}"
+}
+
+for variant in unstamped stamped; do
+	for target in x86_64-unknown-linux-gnu x86_64-pc-windows-msvc x86_64-apple-darwin wasm32-unknown-unknown; do
+		object "$target" "$variant"
+	done
+
+	# Linkers record the paths of their inputs and outputs, so both variants are linked under the same relative paths and renamed afterwards
+	(
+		cd "$work/$variant"
+		"$lld" -flavor gnu -shared --gc-sections -o elf-x86_64.so x86_64-unknown-linux-gnu.o
+		"$lld" -flavor link /nologo /dll /noentry /opt:ref /timestamp:0 /out:pe-x86_64.dll x86_64-pc-windows-msvc.o
+		"$lld" -flavor link /lib /nologo /out:coff-x86_64.lib x86_64-pc-windows-msvc.o
+		"$lld" -flavor darwin -dylib -arch x86_64 -platform_version macos 10.12.0 10.12.0 -dead_strip -no_uuid -o macho-x86_64.dylib x86_64-apple-darwin.o
+
+		# The linkstores aren't referenced by any code, so they would be garbage collected from the module's data segments
+		"$lld" -flavor wasm --no-entry --no-gc-sections -o wasm32.wasm wasm32-unknown-unknown.o
+	)
+
+	for fixture in elf-x86_64.so pe-x86_64.dll coff-x86_64.lib macho-x86_64.dylib wasm32.wasm; do
+		if [ "$variant" = stamped ]; then
+			cp "$work/$variant/$fixture" "$out/${fixture%.*}.stamped.${fixture##*.}"
+		else
+			cp "$work/$variant/$fixture" "$out/$fixture"
+		fi
+	done
+done
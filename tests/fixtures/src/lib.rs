@@ -0,0 +1,23 @@
+//! The linkstores compiled into the fixtures in `tests/fixtures/bin`, which mirror the linkstores in the tests crate.
+//!
+//! Building with `--cfg linkstore_stamped` compiles in the values the tests stamp the fixtures with instead.
+
+#![no_std]
+
+const STAMPED: bool = cfg!(linkstore_stamped);
+
+linkstore::linkstore! {
+	static LINKSTORE_TEST: u64 = if STAMPED { 69 } else { 0xDEADBEEF };
+	static LINKSTORE_YEAH: u32 = if STAMPED { 420 } else { 0xDEADBEEF };
+	static LINKSTORE_SHORTS: [u16; 4] = if STAMPED { [1, 2, 3, 4] } else { [0xDE, 0xAD, 0xBE, 0xEF] };
+	static LINKSTORE_BIG: u128 = if STAMPED { u128::MAX / 2 } else { 0xDEADBEEF };
+}
+
+mod namespaced {
+	linkstore::linkstore! {
+		namespace = "linkstore_tests";
+
+		#[linkstore(name = "app.net.port")]
+		static LINKSTORE_PORT: u16 = 8080;
+	}
+}
@@ -6,6 +6,7 @@ fn main() {
 		let b = *common::LINKSTORE_YEAH::get();
 		let c = a.checked_add(b as u64).unwrap();
 		assert_eq!(c, 69 + 420);
+		assert_eq!(linkstore::pool::get::<u32>("LINKSTORE_INSERTED"), Some(1337));
 	}
 	std::process::exit(123);
 }
@@ -5,3 +5,24 @@ linkstore::linkstore! {
 	pub static LINKSTORE_SHORTS: [u16; 4] = [0xDE, 0xAD, 0xBE, 0xEF];
 	pub static LINKSTORE_BIG: u128 = 0xDEADBEEF;
 }
+
+linkstore::linkstore_pool!(pub LINKSTORE_POOL, 256);
+
+pub mod namespaced {
+	linkstore::linkstore! {
+		namespace = "linkstore_tests";
+
+		pub static LINKSTORE_TEST: u64 = 0xCAFEBABE;
+
+		#[linkstore(name = "app.net.port")]
+		pub static LINKSTORE_PORT: u16 = 8080;
+	}
+}
+
+pub mod sectioned {
+	linkstore::linkstore! {
+		section = ".lnkcfg";
+
+		pub static LINKSTORE_SECTIONED: u32 = 0xFEEDFACE;
+	}
+}